@@ -3,6 +3,8 @@
 
 use std::collections::HashSet;
 
+pub const GLOB_SPECIALS: &str = "\\*?[]";
+
 #[derive(Debug)]
 pub struct PatternElem {
     pub asterisk: bool,
@@ -0,0 +1,73 @@
+//SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use std::ffi::CString;
+use std::mem;
+use nix::libc;
+
+/* POSIX extended regular expression matching with the libc regex functions.
+   Returns the whole match and the captured groups, or None when s does not match. */
+pub fn regex_match(s: &str, pattern: &str) -> Result<Option<Vec<String>>, String> {
+    let c_pattern = CString::new(pattern).map_err(|_| format!("{}: invalid regular expression", pattern))?;
+    let c_s = CString::new(s).map_err(|_| format!("{}: invalid string", s))?;
+
+    unsafe {
+        let mut re: libc::regex_t = mem::zeroed();
+        let err = libc::regcomp(&mut re, c_pattern.as_ptr(), libc::REG_EXTENDED);
+        if err != 0 {
+            let mut buf = [0u8; 256];
+            libc::regerror(err, &re, buf.as_mut_ptr() as *mut libc::c_char, buf.len());
+            let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+            return Err(format!("{}: {}", pattern, String::from_utf8_lossy(&buf[..len])));
+        }
+
+        let num = count_groups(pattern) + 1;
+        let mut matches = vec![libc::regmatch_t{rm_so: -1, rm_eo: -1}; num];
+        let result = libc::regexec(&re, c_s.as_ptr(), num, matches.as_mut_ptr(), 0);
+        libc::regfree(&mut re);
+
+        if result != 0 {
+            return Ok(None);
+        }
+
+        let groups = matches.iter()
+            .map(|m| match m.rm_so {
+                -1 => String::new(),
+                _  => String::from_utf8_lossy(&s.as_bytes()[m.rm_so as usize..m.rm_eo as usize]).to_string(),
+            })
+            .collect();
+        Ok(Some(groups))
+    }
+}
+
+/* the number of parenthesized subexpressions (re_nsub is not exposed by the libc crate) */
+fn count_groups(pattern: &str) -> usize {
+    let mut ans = 0;
+    let mut escaped = false;
+    let mut in_bracket = false;
+    let mut bracket_len = 0;
+
+    for ch in pattern.chars() {
+        if in_bracket {
+            bracket_len += 1;
+            if ch == ']' && bracket_len > 1 {
+                in_bracket = false;
+            }else if ch == '^' && bracket_len == 1 {
+                bracket_len = 0;
+            }
+            continue;
+        }
+
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => {
+                in_bracket = true;
+                bracket_len = 0;
+            },
+            '(' => ans += 1,
+            _ => {},
+        }
+    }
+    ans
+}
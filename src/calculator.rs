@@ -1,133 +1,462 @@
 //SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{ShellCore,Feeder};
+use crate::{ShellCore, Feeder};
+use crate::elements::word::Word;
 
-fn op_order(operator: &String) -> u8 {
-    let op: &str = &operator.clone();
+const OPERATORS: [&str; 39] = [
+    "<<=", ">>=",
+    "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "**", "++", "--",
+    "+=", "-=", "*=", "/=", "%=", "&=", "^=", "|=",
+    "+", "-", "*", "/", "%", "<", ">", "&", "^", "|", "!", "~", "?", ":", ",", "(", ")", "=",
+];
 
-    match op {
-        "**" => 5,
-        "*" | "/" | "%"            => 6, 
-        "+" | "-"                  => 7, 
-        "<<" | ">>"                => 8, 
-        "<=" | ">=" | ">" | "<"    => 9, 
-        "(" | ")"                  => 20, 
-        _ => 255, 
+const ASSIGNMENT_OPS: [&str; 11] = [
+    "=", "+=", "-=", "*=", "/=", "%=", "<<=", ">>=", "&=", "^=", "|=",
+];
+
+/* values of variables are evaluated recursively up to this depth */
+const MAX_RECURSION: usize = 1024;
+
+/* binary operators from the lowest precedence */
+const BINARY_LEVELS: [&[&str]; 10] = [
+    &["||"], &["&&"], &["|"], &["^"], &["&"], &["==", "!="],
+    &["<=", ">=", "<", ">"], &["<<", ">>"], &["+", "-"], &["*", "/", "%"],
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(i64),
+    Name(String, Option<String>), // (name, subscript)
+    Op(String),
+}
+
+struct Calculator<'a> {
+    expression: String,
+    tokens: Vec<(Token, usize)>, // (token, position in the expression)
+    pos: usize,
+    skip: usize, // > 0 while the operands are not evaluated (short-circuit, ?:)
+    depth: usize,
+    core: &'a mut ShellCore,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < expression.len() {
+        let rest = &expression[i..];
+        let ch = rest.chars().next().unwrap();
+
+        if ch.is_whitespace() {
+            i += ch.len_utf8();
+            continue;
+        }
+
+        if ch.is_ascii_digit() {
+            let len = scan_number(rest);
+            let n = parse_number(&rest[..len])
+                .map_err(|msg| format!("{}: {} (error token is \"{}\")", expression.trim(), msg, &rest[..len]))?;
+            tokens.push((Token::Num(n), i));
+            i += len;
+            continue;
+        }
+
+        let mut f = Feeder::new_from(rest.to_string());
+        let len = f.scanner_name(0);
+        if len > 0 {
+            let name = rest[..len].to_string();
+            let (sub, sublen) = match scan_subscript(&rest[len..]) {
+                Some(n) => (Some(rest[len+1..len+n-1].to_string()), n),
+                None => (None, 0),
+            };
+            tokens.push((Token::Name(name, sub), i));
+            i += len + sublen;
+            continue;
+        }
+
+        match OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            Some(op) => {
+                tokens.push((Token::Op(op.to_string()), i));
+                i += op.len();
+            },
+            None => return Err(format!("{}: syntax error: operand expected (error token is \"{}\")", expression, rest)),
+        }
     }
+
+    Ok(tokens)
 }
 
-fn get_integer(text: &mut Feeder) -> Option<(String,u8)> {
-    let pos = text.scanner_integer();
+fn scan_number(text: &str) -> usize {
+    let digits = text.find(|c: char| ! c.is_ascii_digit()).unwrap_or(text.len());
+    let is_digit = |c: char| c.is_ascii_alphanumeric() || c == '@' || c == '_';
 
-    if pos != 0 {
-        Some( (text.consume(pos),0) )
+    let from = match text[digits..].starts_with('#') {
+        true  => digits + 1, // base#digits
+        false => digits,
+    };
+    match text[from..].find(|c: char| ! is_digit(c)) {
+        Some(n) => from + n,
+        None    => text.len(),
+    }
+}
+
+/* decimal, 0x.. (hex), 0.. (octal) and base#digits (base 2-64) */
+fn parse_number(text: &str) -> Result<i64, String> {
+    let (base, digits) = if let Some((b, d)) = text.split_once('#') {
+        match b.parse::<u32>() {
+            Ok(n) if (2..=64).contains(&n) => (n, d),
+            _ => return Err("invalid arithmetic base".to_string()),
+        }
+    }else if text.starts_with("0x") || text.starts_with("0X") {
+        (16, &text[2..])
+    }else if text.starts_with('0') && text.len() > 1 {
+        (8, &text[1..])
     }else{
-        None
+        (10, text)
+    };
+
+    if digits.is_empty() {
+        return Err("invalid integer constant".to_string());
     }
+
+    let mut ans: i64 = 0;
+    for ch in digits.chars() {
+        let d = match ch {
+            '0'..='9' => ch as u32 - '0' as u32,
+            'a'..='z' => ch as u32 - 'a' as u32 + 10,
+            'A'..='Z' if base <= 36 => ch as u32 - 'A' as u32 + 10,
+            'A'..='Z' => ch as u32 - 'A' as u32 + 36,
+            '@' => 62,
+            '_' => 63,
+            _ => 64,
+        };
+        if d >= base {
+            return Err("value too great for base".to_string());
+        }
+        ans = ans.wrapping_mul(base as i64).wrapping_add(d as i64);
+    }
+    Ok(ans)
 }
 
-fn get_operator(text: &mut Feeder) -> Option<(String,u8)> {
-    if text.len() == 0 {
+/* length of [...] at the head of the text */
+fn scan_subscript(text: &str) -> Option<usize> {
+    if ! text.starts_with('[') {
         return None;
     }
 
-    if let Some(_) = "+-/%*".find(text.nth(0)) {
-        let op = text.consume(1);
-        Some( (op.clone(), op_order(&op)) )
-    }else{
-        None
+    let mut depth = 0;
+    for (i, ch) in text.char_indices() {
+        match ch {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i+1);
+                }
+            },
+            _ => {},
+        }
     }
+    None
 }
 
-fn reduce(stack: &mut Vec<i32>, op: String ) {
-    let op: &str = &op.clone();
+impl Calculator<'_> {
+    fn peek_op(&self) -> Option<&str> {
+        match self.tokens.get(self.pos) {
+            Some((Token::Op(op), _)) => Some(op),
+            _ => None,
+        }
+    }
 
-    let right = stack.pop().unwrap();
-    let left = stack.pop().unwrap();
+    fn consume_op(&mut self, ops: &[&str]) -> Option<String> {
+        let op = self.peek_op().filter(|op| ops.contains(op))?.to_string();
+        self.pos += 1;
+        Some(op)
+    }
 
-    match op {
-        "+" => stack.push(left+right),
-        "-" => stack.push(left-right),
-        "*" => stack.push(left*right),
-        "/" => stack.push(left/right),
-        _ => (), 
+    fn error_token(&self) -> String {
+        self.error_token_at(self.pos)
     }
-}
 
-pub fn calculate(expression: String, core: &mut ShellCore) -> String {
-    let tokens = tokenizer(expression, core);
-    let mut num_stack: Vec<i32> = vec![];
-    let mut wait_stack: Vec<(String,u8)> = vec![];
-
-    //eprintln!("TOKENS: {:?}", tokens);
-
-    for t in tokens {
-     //   eprintln!("STACK: {:?}", num_stack);
-      //  eprintln!("WAIT STACK: {:?}", wait_stack);
-        while wait_stack.len() != 0 {
-            let wtop = wait_stack.pop().unwrap();
-
-            if t.0 == ")" && wtop.0 == "(" {
-            //    wait_stack.push(wtop);
-                break;
-            }
-
-            if wtop.1 <= t.1 {
-                if wtop.1 > 0 {
-                    reduce(&mut num_stack, wtop.0.clone());
-                }else{
-                    num_stack.push(wtop.0.parse::<i32>().unwrap());
-                }
-            }else{
-                wait_stack.push(wtop);
-                break;
-            }
+    fn error_token_at(&self, index: usize) -> String {
+        match self.tokens.get(index) {
+            Some((_, p)) => self.expression[*p..].to_string(),
+            None => match self.tokens.last() {
+                Some((_, p)) => self.expression[*p..].to_string(),
+                None => String::new(),
+            },
         }
+    }
 
-        if t.0 != ")" {
-            wait_stack.push(t);
+    fn syntax_error(&self, msg: &str) -> String {
+        format!("{}: syntax error: {} (error token is \"{}\")", self.expression.trim(), msg, self.error_token())
+    }
+
+    fn comma(&mut self) -> Result<i64, String> {
+        let mut ans = self.assignment()?;
+        while self.consume_op(&[","]).is_some() {
+            ans = self.assignment()?;
         }
+        Ok(ans)
     }
 
-//    eprintln!("-------------------");
-    while wait_stack.len() != 0 {
-        let wtop = wait_stack.pop().unwrap();
-        if wtop.1 > 0 {
-            reduce(&mut num_stack, wtop.0.clone());
-        }else{
-            num_stack.push(wtop.0.parse::<i32>().unwrap());
-        //    stack.push(wtop);
+    fn assignment(&mut self) -> Result<i64, String> {
+        let (name, sub) = match self.tokens.get(self.pos).cloned() {
+            Some((Token::Name(n, s), _)) => (n, s),
+            _ => return self.non_assignment(),
+        };
+
+        let op = match self.tokens.get(self.pos+1) {
+            Some((Token::Op(op), _)) if ASSIGNMENT_OPS.contains(&op.as_str()) => op.clone(),
+            _ => return self.non_assignment(),
+        };
+        self.pos += 2;
+
+        let right_pos = self.pos;
+        let right = self.assignment()?;
+        if self.skip > 0 {
+            return Ok(0);
         }
- //       eprintln!("STACK: {:?}", num_stack);
-  //      eprintln!("WAIT STACK: {:?}", wait_stack);
+
+        let ans = match op.as_str() {
+            "=" => right,
+            _ => {
+                let left = self.value_of(&name, &sub)?;
+                self.apply(&op[..op.len()-1], left, right, right_pos)?
+            },
+        };
+        self.set_value(&name, &sub, ans)?;
+        Ok(ans)
     }
 
-    //stack.iter().map(|t| t.0.clone()).collect::<Vec<String>>().join(" ")
-    num_stack.pop().unwrap().to_string()
-}
+    fn non_assignment(&mut self) -> Result<i64, String> {
+        let ans = self.ternary()?;
+        if self.consume_op(&ASSIGNMENT_OPS).is_some() {
+            self.pos -= 1;
+            return Err(format!("{}: attempted assignment to non-variable (error token is \"{}\")",
+                               self.expression.trim(), self.error_token()));
+        }
+        Ok(ans)
+    }
 
-fn tokenizer(expression: String, _core: &mut ShellCore) -> Vec<(String,u8)> {
-    //let mut stack = vec![];
-    let mut tokens = vec![];
-    
-    let mut text = Feeder::new_from(expression.clone());
-    while text.len() != 0 {
-        //get value
-        if let Some(n) = get_integer(&mut text) {
-            tokens.push(n);
-        }else{
-            break;
+    fn ternary(&mut self) -> Result<i64, String> {
+        let cond = self.binary(0)?;
+        if self.consume_op(&["?"]).is_none() {
+            return Ok(cond);
+        }
+
+        if cond == 0 { self.skip += 1; }
+        let left = self.comma()?;
+        if cond == 0 { self.skip -= 1; }
+
+        if self.consume_op(&[":"]).is_none() {
+            return Err(self.syntax_error("`:' expected for conditional expression"));
         }
 
-        //get operator
-        if let Some(op) = get_operator(&mut text) {
-            tokens.push(op);
-        }else{
-            break;
+        if cond != 0 { self.skip += 1; }
+        let right = self.ternary()?;
+        if cond != 0 { self.skip -= 1; }
+
+        Ok(if cond != 0 { left } else { right })
+    }
+
+    fn binary(&mut self, level: usize) -> Result<i64, String> {
+        if level == BINARY_LEVELS.len() {
+            return self.power();
+        }
+
+        let mut left = self.binary(level+1)?;
+        while let Some(op) = self.consume_op(BINARY_LEVELS[level]) {
+            /* the right side of && and || is not evaluated when the left decides the result */
+            let short_circuit = (op == "&&" && left == 0) || (op == "||" && left != 0);
+            if short_circuit { self.skip += 1; }
+            let right_pos = self.pos;
+            let right = self.binary(level+1)?;
+            if short_circuit { self.skip -= 1; }
+
+            left = self.apply(&op, left, right, right_pos)?;
+        }
+        Ok(left)
+    }
+
+    fn apply(&self, op: &str, left: i64, right: i64, right_pos: usize) -> Result<i64, String> {
+        let ans = match op {
+            "||" => (left != 0 || right != 0) as i64,
+            "&&" => (left != 0 && right != 0) as i64,
+            "|"  => left | right,
+            "^"  => left ^ right,
+            "&"  => left & right,
+            "==" => (left == right) as i64,
+            "!=" => (left != right) as i64,
+            "<=" => (left <= right) as i64,
+            ">=" => (left >= right) as i64,
+            "<"  => (left < right) as i64,
+            ">"  => (left > right) as i64,
+            "<<" => left.wrapping_shl(right as u32),
+            ">>" => left.wrapping_shr(right as u32),
+            "+"  => left.wrapping_add(right),
+            "-"  => left.wrapping_sub(right),
+            "*"  => left.wrapping_mul(right),
+            "/" | "%" if right == 0 => match self.skip {
+                0 => return Err(format!("{}: division by 0 (error token is \"{}\")",
+                                        self.expression.trim(), self.error_token_at(right_pos))),
+                _ => 0,
+            },
+            "/"  => left.wrapping_div(right),
+            "%"  => left.wrapping_rem(right),
+            _    => return Err(self.syntax_error("invalid arithmetic operator")),
+        };
+        Ok(ans)
+    }
+
+    fn power(&mut self) -> Result<i64, String> {
+        let base = self.unary()?;
+        if self.consume_op(&["**"]).is_none() {
+            return Ok(base);
+        }
+
+        let exp_pos = self.pos;
+        let exp = self.power()?;
+        if exp < 0 {
+            return match self.skip {
+                0 => Err(format!("{}: exponent less than 0 (error token is \"{}\")",
+                                 self.expression.trim(), self.error_token_at(exp_pos))),
+                _ => Ok(0),
+            };
+        }
+        Ok(base.wrapping_pow(exp as u32))
+    }
+
+    fn unary(&mut self) -> Result<i64, String> {
+        if let Some(op) = self.consume_op(&["++", "--"]) {
+            let (name, sub) = match self.tokens.get(self.pos).cloned() {
+                Some((Token::Name(n, s), _)) => (n, s),
+                /* such as --1, which is two unary minuses */
+                _ => return self.unary(),
+            };
+            self.pos += 1;
+            return self.increment(&name, &sub, &op, true);
+        }
+
+        match self.consume_op(&["+", "-", "!", "~"]).as_deref() {
+            Some("+") => self.unary(),
+            Some("-") => Ok(self.unary()?.wrapping_neg()),
+            Some("!") => Ok((self.unary()? == 0) as i64),
+            Some("~") => Ok(! self.unary()?),
+            _ => self.primary(),
         }
     }
 
-    tokens
+    fn increment(&mut self, name: &str, sub: &Option<String>, op: &str, prefix: bool) -> Result<i64, String> {
+        if self.skip > 0 {
+            return Ok(0);
+        }
+
+        let old = self.value_of(name, sub)?;
+        let new = match op {
+            "++" => old.wrapping_add(1),
+            _    => old.wrapping_sub(1),
+        };
+        self.set_value(name, sub, new)?;
+        Ok(if prefix { new } else { old })
+    }
+
+    fn index(&mut self, sub: &str) -> Result<i64, String> {
+        evaluate_at(sub.to_string(), self.core, self.depth+1)
+    }
+
+    fn value_of(&mut self, name: &str, sub: &Option<String>) -> Result<i64, String> {
+        if self.skip > 0 {
+            return Ok(0);
+        }
+
+        let value = match sub {
+            None => self.core.get_var(name),
+            Some(s) if self.core.is_assoc(name) => self.core.get_assoc_elem(name, s.trim()),
+            Some(s) => {
+                let i = self.index(s)?;
+                self.core.get_array_elem(name, i)
+            },
+        };
+
+        let value = value.trim();
+        if let Ok(n) = value.parse::<i64>() {
+            return Ok(n);
+        }
+        if self.depth >= MAX_RECURSION {
+            return Err(format!("{}: expression recursion level exceeded (error token is \"{}\")", value, value));
+        }
+        evaluate_at(value.to_string(), self.core, self.depth+1)
+    }
+
+    fn set_value(&mut self, name: &str, sub: &Option<String>, value: i64) -> Result<(), String> {
+        match sub {
+            None => self.core.set_var(name, &value.to_string()),
+            Some(s) if self.core.is_assoc(name) => self.core.set_assoc_elem(name, s.trim(), &value.to_string()),
+            Some(s) => {
+                let i = self.index(s)?;
+                self.core.set_array_elem(name, i, &value.to_string())?;
+            },
+        }
+        Ok(())
+    }
+
+    fn primary(&mut self) -> Result<i64, String> {
+        match self.tokens.get(self.pos).cloned() {
+            Some((Token::Num(n), _)) => {
+                self.pos += 1;
+                Ok(n)
+            },
+            Some((Token::Name(name, sub), _)) => {
+                self.pos += 1;
+                match self.consume_op(&["++", "--"]) {
+                    Some(op) => self.increment(&name, &sub, &op, false),
+                    None => self.value_of(&name, &sub),
+                }
+            },
+            Some((Token::Op(op), _)) if op == "(" => {
+                self.pos += 1;
+                let ans = self.comma()?;
+                if self.consume_op(&[")"]).is_none() {
+                    return Err(self.syntax_error("missing `)'"));
+                }
+                Ok(ans)
+            },
+            _ => Err(self.syntax_error("operand expected")),
+        }
+    }
 }
 
+fn evaluate_at(expression: String, core: &mut ShellCore, depth: usize) -> Result<i64, String> {
+    let tokens = tokenize(&expression)?;
+    if tokens.is_empty() {
+        return Ok(0);
+    }
+
+    let mut calc = Calculator {
+        expression,
+        tokens,
+        pos: 0,
+        skip: 0,
+        depth,
+        core,
+    };
+
+    let ans = calc.comma()?;
+    if calc.pos < calc.tokens.len() {
+        return Err(calc.syntax_error("invalid arithmetic operator"));
+    }
+    Ok(ans)
+}
+
+/* for an expression whose expansions are already done, such as an argument of let */
+pub fn evaluate(expression: &str, core: &mut ShellCore) -> Result<i64, String> {
+    evaluate_at(expression.to_string(), core, 0)
+}
+
+/* parameter expansions and quotes in the expression are processed before the evaluation */
+pub fn calculate(expression: String, core: &mut ShellCore) -> Result<String, String> {
+    let expanded = Word::parse_expansions(&expression, core).eval_string(core);
+    Ok(evaluate_at(expanded, core, 0)?.to_string())
+}
@@ -5,7 +5,7 @@ pub mod builtins;
 pub mod shopts;
 pub mod job;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, BTreeMap};
 use std::fs::File;
 use std::env;
 use crate::core::shopts::Shopts;
@@ -13,13 +13,14 @@ use crate::core::job::Job;
 use nix::sys::wait::{waitpid, WaitStatus, WaitPidFlag};
 use nix::unistd::Pid;
 
-use nix::unistd::read;
+use nix::unistd::{close, read};
 use std::os::unix::prelude::RawFd;
 
 pub struct ShellCore {
     pub builtins: HashMap<String, fn(&mut ShellCore, args: &mut Vec<String>) -> i32>,
     pub functions: HashMap<String, String>,
-    pub arrays: HashMap<String, Vec<String>>,
+    pub arrays: HashMap<String, BTreeMap<usize, String>>,
+    pub assoc_arrays: HashMap<String, BTreeMap<String, String>>,
     pub vars: HashMap<String, String>,
     pub args: Vec<String>,
     pub aliases: HashMap<String, String>,
@@ -31,6 +32,12 @@ pub struct ShellCore {
     pub script_file: Option<File>,
     pub return_enable: bool,
     pub return_flag: bool,
+    pub loop_level: usize,
+    pub break_counter: usize,
+    pub continue_counter: usize,
+    pub eval_error: bool, // set when an expansion fails
+    pub proc_subs: Vec<(RawFd, Pid)>, // fds on the shell side and pids of process substitutions
+    pub proc_sub_pids: Vec<Pid>, // process substitutions not reaped yet
     pub shopts: Shopts, 
 }
 
@@ -40,6 +47,7 @@ impl ShellCore {
             builtins: HashMap::new(),
             functions: HashMap::new(),
             arrays: HashMap::new(),
+            assoc_arrays: HashMap::new(),
             vars: HashMap::new(),
             args: vec![],
             aliases: HashMap::new(),
@@ -51,6 +59,12 @@ impl ShellCore {
             script_file: None,
             return_flag: false,
             return_enable: false,
+            loop_level: 0,
+            break_counter: 0,
+            continue_counter: 0,
+            eval_error: false,
+            proc_subs: vec![],
+            proc_sub_pids: vec![],
             shopts: Shopts::new(),
         };
 
@@ -61,55 +75,203 @@ impl ShellCore {
     }
 
     pub fn set_var(&mut self, key: &str, value: &str) {
+        if let Some(a) = self.arrays.get_mut(key) {
+            a.insert(0, value.to_string());
+            return;
+        }
+        if let Some(m) = self.assoc_arrays.get_mut(key) {
+            m.insert("0".to_string(), value.to_string());
+            return;
+        }
         self.vars.insert(key.to_string(), value.to_string());
     }
 
+    pub fn set_array(&mut self, name: &str, values: Vec<String>) {
+        self.vars.remove(name);
+        self.arrays.insert(name.to_string(), values.into_iter().enumerate().collect());
+    }
+
+    /* a scalar variable turns into an array whose 0th element is its value */
+    fn array_mut(&mut self, name: &str) -> &mut BTreeMap<usize, String> {
+        if ! self.arrays.contains_key(name) {
+            let mut a = BTreeMap::new();
+            if let Some(v) = self.vars.remove(name) {
+                a.insert(0, v);
+            }
+            self.arrays.insert(name.to_string(), a);
+        }
+        self.arrays.get_mut(name).unwrap()
+    }
+
+    /* a negative index counts from the end of the array */
+    pub fn array_index(&self, name: &str, index: i64) -> Option<usize> {
+        if index >= 0 {
+            return Some(index as usize);
+        }
+
+        let len = match self.arrays.get(name).and_then(|a| a.keys().next_back()) {
+            Some(max) => *max as i64 + 1,
+            None      => 0,
+        };
+        match len + index {
+            n if n >= 0 => Some(n as usize),
+            _ => None,
+        }
+    }
+
+    pub fn set_array_elem(&mut self, name: &str, index: i64, value: &str) -> Result<(), String> {
+        let i = match self.array_index(name, index) {
+            Some(i) => i,
+            None    => return Err(format!("{}[{}]: bad array subscript", name, index)),
+        };
+        self.array_mut(name).insert(i, value.to_string());
+        Ok(())
+    }
+
+    pub fn append_array(&mut self, name: &str, values: Vec<String>) {
+        let a = self.array_mut(name);
+        let next = a.keys().next_back().map_or(0, |max| max + 1);
+        a.extend((next..).zip(values));
+    }
+
+    pub fn get_array_elem(&self, name: &str, index: i64) -> String {
+        self.find_array_elem(name, index).unwrap_or_default()
+    }
+
+    /* None if the element is unset */
+    pub fn find_array_elem(&self, name: &str, index: i64) -> Option<String> {
+        if ! self.arrays.contains_key(name) {
+            return match index {
+                0 | -1 => self.find_var(name),
+                _ => None,
+            };
+        }
+
+        self.array_index(name, index)
+            .and_then(|i| self.arrays[name].get(&i).cloned())
+    }
+
+    pub fn is_assoc(&self, name: &str) -> bool {
+        self.assoc_arrays.contains_key(name)
+    }
+
+    pub fn set_assoc_elem(&mut self, name: &str, key: &str, value: &str) {
+        self.assoc_arrays.entry(name.to_string()).or_default()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    pub fn get_assoc_elem(&self, name: &str, key: &str) -> String {
+        self.find_assoc_elem(name, key).unwrap_or_default()
+    }
+
+    pub fn find_assoc_elem(&self, name: &str, key: &str) -> Option<String> {
+        self.assoc_arrays.get(name).and_then(|m| m.get(key).cloned())
+    }
+
+    pub fn unset_assoc_elem(&mut self, name: &str, key: &str) {
+        if let Some(m) = self.assoc_arrays.get_mut(name) {
+            m.remove(key);
+        }
+    }
+
+    pub fn get_array_values(&self, name: &str) -> Vec<String> {
+        if let Some(a) = self.arrays.get(name) {
+            return a.values().cloned().collect();
+        }
+        if let Some(m) = self.assoc_arrays.get(name) {
+            return m.values().cloned().collect();
+        }
+
+        match self.get_var(name).as_str() {
+            "" => vec![],
+            v  => vec![v.to_string()],
+        }
+    }
+
+    pub fn get_array_indices(&self, name: &str) -> Vec<String> {
+        if let Some(a) = self.arrays.get(name) {
+            return a.keys().map(|k| k.to_string()).collect();
+        }
+        if let Some(m) = self.assoc_arrays.get(name) {
+            return m.keys().cloned().collect();
+        }
+
+        match self.get_var(name).as_str() {
+            "" => vec![],
+            _  => vec!["0".to_string()],
+        }
+    }
+
+    pub fn unset_array_elem(&mut self, name: &str, index: i64) -> Result<(), String> {
+        if ! self.arrays.contains_key(name) {
+            if index == 0 {
+                self.vars.remove(name);
+            }
+            return Ok(());
+        }
+
+        match self.array_index(name, index) {
+            Some(i) => {
+                self.arrays.get_mut(name).unwrap().remove(&i);
+                Ok(())
+            },
+            None => Err(format!("{}[{}]: bad array subscript", name, index)),
+        }
+    }
+
     pub fn get_var(&self, key: &str) -> String {
+        self.find_var(key).unwrap_or_default()
+    }
+
+    /* None if the variable is unset */
+    pub fn find_var(&self, key: &str) -> Option<String> {
         if let Ok(n) = key.parse::<usize>() {
-            if self.args.len() > n {
-                return self.args[n].clone();
-            }
+            return self.args.get(n).cloned();
         }
 
         if key == "-" {
-            return self.flags.clone();
+            return Some(self.flags.clone());
         }
 
         if key == "#" {
-            return (self.args.len() - 1).to_string();
+            return Some((self.args.len() - 1).to_string());
         }
 
         if key == "@" {
             if self.args.len() == 1 {
-                return "".to_string();
+                return Some("".to_string());
             }
 
-            return self.args[1..].to_vec().join(" ");
+            return Some(self.args[1..].to_vec().join(" "));
         }
 
         if key == "*" {
             if self.args.len() == 1 {
-                return "".to_string();
+                return Some("".to_string());
             }
 
             if self.in_double_quot {
                 if let Some(ch) = self.get_var("IFS").chars().nth(0){
-                    return self.args[1..].to_vec().join(&ch.to_string());
+                    return Some(self.args[1..].to_vec().join(&ch.to_string()));
                 }
             }
 
-            return self.args[1..].to_vec().join(" ");
+            return Some(self.args[1..].to_vec().join(" "));
         }
 
-        if let Some(s) = self.vars.get(&key as &str){
-            return s.to_string();
+        if let Some(s) = self.vars.get(key){
+            return Some(s.to_string());
         };
 
-        if let Ok(s) = env::var(&key) {
-            return s.to_string();
+        if let Some(a) = self.arrays.get(key){
+            return a.get(&0).cloned();
         };
 
-        "".to_string()
+        if let Some(m) = self.assoc_arrays.get(key){
+            return m.get("0").cloned();
+        };
+
+        env::var(key).ok()
     }
 
     pub fn get_function(&mut self, name: &String) -> Option<String> {
@@ -131,6 +293,21 @@ impl ShellCore {
         }
     }
 
+    /* called by a loop after its body: true if the loop must be left */
+    pub fn check_loop_exit(&mut self) -> bool {
+        if self.break_counter > 0 {
+            self.break_counter -= 1;
+            return true;
+        }
+
+        if self.continue_counter > 0 {
+            self.continue_counter -= 1;
+            return self.continue_counter > 0;
+        }
+
+        false
+    }
+
     pub fn has_flag(&self, flag: char) -> bool {
         if let Some(_) = self.flags.find(flag) {
             return true;
@@ -169,6 +346,18 @@ impl ShellCore {
         self.set_var("?", &exit_status.to_string());
     } 
 
+    /* closes the fds of process substitutions opened after the first `from` ones */
+    pub fn close_process_substitutions(&mut self, from: usize) {
+        for (fd, pid) in self.proc_subs.split_off(from) {
+            let _ = close(fd);
+            self.proc_sub_pids.push(pid);
+        }
+
+        self.proc_sub_pids.retain(|pid| {
+            matches!(waitpid(*pid, Some(WaitPidFlag::WNOHANG)), Ok(WaitStatus::StillAlive))
+        });
+    }
+
     pub fn read_pipe(&mut self, pin: RawFd, pid: Pid) -> String {
         let mut ans = "".to_string();
         let mut ch = [0;1000];
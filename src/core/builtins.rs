@@ -7,7 +7,9 @@ use std::path::Path;
 use std::fs::OpenOptions;
 use std::io::{Write, BufReader, BufRead};
 use crate::bash_glob::glob_match;
+use crate::calculator::evaluate;
 use crate::elements::command::CommandType;
+use crate::elements::subscript::Subscript;
 
 use crate::Script;
 use crate::ShellCore;
@@ -17,19 +19,25 @@ pub fn set_builtins(core: &mut ShellCore){
     core.builtins.insert(".".to_string(), source);
     core.builtins.insert(":".to_string(), true_);
     core.builtins.insert("alias".to_string(), alias);
+    core.builtins.insert("break".to_string(), break_);
     core.builtins.insert("builtin".to_string(), builtin);
     core.builtins.insert("cd".to_string(), cd);
+    core.builtins.insert("continue".to_string(), continue_);
+    core.builtins.insert("declare".to_string(), declare);
     core.builtins.insert("eval".to_string(), eval);
     core.builtins.insert("exit".to_string(), exit);
     core.builtins.insert("export".to_string(), export);
     core.builtins.insert("false".to_string(), false_);
     core.builtins.insert("history".to_string(), history);
     core.builtins.insert("jobs".to_string(), jobs);
+    core.builtins.insert("let".to_string(), let_);
     core.builtins.insert("pwd".to_string(), pwd);
     core.builtins.insert("set".to_string(), set);
     core.builtins.insert("shift".to_string(), shift);
     core.builtins.insert("true".to_string(), true_);
     core.builtins.insert("read".to_string(), read);
+    core.builtins.insert("typeset".to_string(), declare);
+    core.builtins.insert("unset".to_string(), unset);
     core.builtins.insert("return".to_string(), return_);
     core.builtins.insert("shopt".to_string(), shopt);
     core.builtins.insert("source".to_string(), source);
@@ -39,8 +47,10 @@ pub fn set_builtins(core: &mut ShellCore){
     core.builtins.insert(".".to_string(), source);
     core.builtins.insert(":".to_string(), true_);
     core.builtins.insert("alias".to_string(), alias);
+    core.builtins.insert("break".to_string(), break_);
     core.builtins.insert("builtin".to_string(), builtin);
     core.builtins.insert("cd".to_string(), cd);
+    core.builtins.insert("continue".to_string(), continue_);
     core.builtins.insert("eval".to_string(), eval);
     core.builtins.insert("exit".to_string(), exit);
     core.builtins.insert("export".to_string(), export);
@@ -218,6 +228,14 @@ pub fn alias(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     0
 }
 
+fn set_option(core: &mut ShellCore, flag: char, enable: bool) {
+    if enable && ! core.has_flag(flag) {
+        core.flags.push(flag);
+    }else if ! enable {
+        core.flags.retain(|c| c != flag);
+    }
+}
+
 pub fn set(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     if args.len() == 1 {
        for k in core.vars.keys() {
@@ -226,12 +244,47 @@ pub fn set(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
        return 0;
     }
 
-    core.args.clear();
-
-    for a in args {
-        core.args.push(a.to_string());
+    let mut i = 1;
+    let mut positional = false;
+    while i < args.len() {
+        let a = args[i].clone();
+        let enable = a.starts_with('-');
+        if a == "--" {
+            positional = true;
+            i += 1;
+            break;
+        }else if a == "-o" || a == "+o" {
+            let flag = match args.get(i+1).map(|s| s.as_str()) {
+                Some("nounset") => 'u',
+                Some("verbose") => 'v',
+                Some("xtrace")  => 'x',
+                Some(name) => {
+                    eprintln!("bash: set: {}: invalid option name", name);
+                    return 1;
+                },
+                None => return 0,
+            };
+            set_option(core, flag, enable);
+            i += 2;
+        }else if a.len() > 1 && (a.starts_with('-') || a.starts_with('+')) {
+            for flag in a[1..].chars() {
+                if ! "uvx".contains(flag) {
+                    eprintln!("bash: set: {}{}: invalid option", &a[..1], flag);
+                    return 2;
+                }
+                set_option(core, flag, enable);
+            }
+            i += 1;
+        }else{
+            positional = true;
+            break;
+        }
     }
 
+    if positional {
+        core.args.truncate(1);
+        core.args.extend(args[i..].iter().cloned());
+    }
     0
 }
 
@@ -309,6 +362,42 @@ pub fn return_(core: &mut ShellCore, _args: &mut Vec<String>) -> i32 {
     }
 }
 
+/* returns the number of loops to leave and the exit status */
+fn loop_count(core: &mut ShellCore, args: &[String]) -> (usize, i32) {
+    if core.loop_level == 0 {
+        eprintln!("bash: {}: only meaningful in a `for', `while', or `until' loop", args[0]);
+        return (0, 0);
+    }
+
+    if args.len() < 2 {
+        return (1, 0);
+    }
+
+    match args[1].parse::<i64>() {
+        Ok(n) if n > 0 => (std::cmp::min(n as usize, core.loop_level), 0),
+        Ok(_) => {
+            eprintln!("bash: {}: {}: loop count out of range", args[0], args[1]);
+            (1, 1)
+        },
+        Err(_) => {
+            eprintln!("bash: {}: {}: numeric argument required", args[0], args[1]);
+            (0, 1)
+        },
+    }
+}
+
+pub fn break_(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (n, status) = loop_count(core, args);
+    core.break_counter = n;
+    status
+}
+
+pub fn continue_(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (n, status) = loop_count(core, args);
+    core.continue_counter = n;
+    status
+}
+
 pub fn jobs(core: &mut ShellCore, _args: &mut Vec<String>) -> i32 {
     for j in 1..core.jobs.len() {
         if core.jobs[j].async_pids.len() != 0 {
@@ -384,6 +473,151 @@ pub fn export(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     0
 }
 
+pub fn declare(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut assoc = false;
+    let mut indexed = false;
+    let mut status = 0;
+
+    for a in args[1..].iter() {
+        if let Some(opts) = a.strip_prefix('-') {
+            for ch in opts.chars() {
+                match ch {
+                    'A' => assoc = true,
+                    'a' => indexed = true,
+                    _ => {
+                        eprintln!("bash: declare: -{}: invalid option", ch);
+                        return 2;
+                    },
+                }
+            }
+            continue;
+        }
+
+        let (name, value) = match a.find('=') {
+            Some(p) => (&a[..p], Some(&a[p+1..])),
+            None    => (a.as_str(), None),
+        };
+
+        if assoc && core.arrays.contains_key(name) {
+            eprintln!("bash: declare: {}: cannot convert indexed to associative array", name);
+            status = 1;
+            continue;
+        }
+        if indexed && core.is_assoc(name) {
+            eprintln!("bash: declare: {}: cannot convert associative to indexed array", name);
+            status = 1;
+            continue;
+        }
+
+        if assoc && ! core.is_assoc(name) {
+            core.assoc_arrays.insert(name.to_string(), Default::default());
+            if let Some(v) = core.vars.remove(name) {
+                core.set_assoc_elem(name, "0", &v);
+            }
+        }else if indexed {
+            core.append_array(name, vec![]);
+        }
+
+        if let Some(v) = value {
+            core.set_var(name, v);
+        }
+    }
+    status
+}
+
+fn unset_array_elem(core: &mut ShellCore, name: &str, subscript: &str) -> i32 {
+    let mut feeder = Feeder::new_from(subscript.to_string());
+    let mut sub = match Subscript::parse(&mut feeder, core) {
+        Some(s) if feeder.len() == 0 => s,
+        _ => {
+            eprintln!("bash: unset: `{}{}': not a valid identifier", name, subscript);
+            return 1;
+        },
+    };
+
+    if sub.is_all() {
+        core.arrays.remove(name);
+        core.assoc_arrays.remove(name);
+        core.vars.remove(name);
+        return 0;
+    }
+
+    if core.is_assoc(name) {
+        return match sub.eval_key(core) {
+            Ok(key) => {
+                core.unset_assoc_elem(name, &key);
+                0
+            },
+            Err(msg) => {
+                eprintln!("bash: unset: {}", msg);
+                1
+            },
+        };
+    }
+
+    let result = match sub.eval_index(core) {
+        Ok(index) => core.unset_array_elem(name, index),
+        Err(msg)  => Err(msg),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(msg) => {
+            eprintln!("bash: unset: {}", msg);
+            1
+        },
+    }
+}
+
+pub fn unset(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut function = false;
+    let mut status = 0;
+
+    for a in args[1..].iter() {
+        match a.as_str() {
+            "-f" => function = true,
+            "-v" => function = false,
+            _ if function => {
+                core.functions.remove(a);
+            },
+            _ => {
+                if let Some(pos) = a.find('[') {
+                    status |= unset_array_elem(core, &a[..pos], &a[pos..]);
+                }else if core.vars.contains_key(a) || core.arrays.contains_key(a) || core.is_assoc(a) {
+                    core.vars.remove(a);
+                    core.arrays.remove(a);
+                    core.assoc_arrays.remove(a);
+                }else if env::var(a).is_ok() {
+                    env::remove_var(a);
+                }else{
+                    core.functions.remove(a);
+                }
+            },
+        }
+    }
+    status
+}
+
+pub fn let_(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if args.len() == 1 {
+        eprintln!("bash: let: expression expected");
+        return 1;
+    }
+
+    let mut last = 0;
+    for a in args[1..].iter() {
+        match evaluate(a, core) {
+            Ok(n) => last = n,
+            Err(msg) => {
+                eprintln!("bash: let: {}", msg);
+                return 1;
+            },
+        }
+    }
+
+    if last == 0 { 1 } else { 0 }
+}
+
 pub fn eval(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     if args.len() == 1 {
         return 0;
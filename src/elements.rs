@@ -8,7 +8,10 @@ pub mod command;
 pub mod word;
 pub mod substitution;
 pub mod value;
+pub mod subscript;
 
 pub mod subword;
 
 pub mod redirect;
+pub mod here_document;
+pub mod cond_expr;
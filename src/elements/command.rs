@@ -5,9 +5,13 @@ pub mod simple;
 pub mod paren;
 pub mod brace;
 pub mod double_paren;
+pub mod double_bracket;
 pub mod if_command;
 pub mod case_command;
 pub mod while_command;
+pub mod for_command;
+pub mod arith_for_command;
+pub mod select_command;
 pub mod function_definition;
 
 use nix::unistd::Pid;
@@ -15,10 +19,17 @@ use nix::unistd::Pid;
 use std::os::unix::prelude::RawFd;
 
 use crate::{Feeder, ShellCore}; 
+use crate::elements::script::Script;
+use crate::elements::word::Word;
+use crate::utils::eval_glob;
 
 use self::double_paren::CommandDoubleParen;
+use self::double_bracket::CommandDoubleBracket;
 use self::if_command::CommandIf;
 use self::while_command::CommandWhile;
+use self::for_command::CommandFor;
+use self::arith_for_command::CommandArithFor;
+use self::select_command::CommandSelect;
 use self::paren::CommandParen;
 use self::brace::CommandBrace;
 use self::case_command::CommandCase;
@@ -32,6 +43,8 @@ use nix::unistd::{close, fork, ForkResult};
 pub enum CommandType {
     Case,
     While,
+    For,
+    Select,
     If,
     Paren,
     //DoubleParen,
@@ -86,11 +99,80 @@ pub trait Command {
 pub fn parse(text: &mut Feeder, conf: &mut ShellCore) -> Option<Box<dyn Command>> {
     if let Some(a) =      CommandIf::parse(text,conf)                  {Some(Box::new(a))}
     else if let Some(a) = CommandWhile::parse(text, conf)              {Some(Box::new(a))}
+    else if let Some(a) = CommandFor::parse(text, conf)                {Some(Box::new(a))}
+    else if let Some(a) = CommandArithFor::parse(text, conf)           {Some(Box::new(a))}
+    else if let Some(a) = CommandSelect::parse(text, conf)             {Some(Box::new(a))}
     else if let Some(a) = CommandCase::parse(text, conf)               {Some(Box::new(a))}
-    else if let Some(a) = CommandParen::parse(text, conf, false)       {Some(Box::new(a))}
     else if let Some(a) = CommandDoubleParen::parse(text, conf, false) {Some(Box::new(a))}
+    else if let Some(a) = CommandParen::parse(text, conf, false)       {Some(Box::new(a))}
+    else if let Some(a) = CommandDoubleBracket::parse(text, conf)      {Some(Box::new(a))}
     else if let Some(a) = CommandBrace::parse(text, conf)              {Some(Box::new(a))}
     else if let Some(a) = FunctionDefinition::parse(text, conf)        {Some(Box::new(a))}
     else if let Some(a) = SimpleCommand::parse(text, conf)             {Some(Box::new(a))}
     else {None}
 }
+
+/* the words after "in" of for and select */
+pub fn eval_words(words: &mut [Word], conf: &mut ShellCore) -> Vec<String> {
+    let mut values = vec![];
+
+    for word in words.iter_mut() {
+        for s in &word.eval(conf) {
+            values.append(&mut eval_glob(s));
+        }
+    };
+
+    values
+}
+
+/* None if the command has no "in" part */
+pub fn parse_in_words(text: &mut Feeder, conf: &mut ShellCore, ans_text: &mut String) -> Option<Vec<Word>> {
+    if ! text.starts_with("in") {
+        return None;
+    }
+    let backup = text.clone();
+    let mut consumed = text.consume(2);
+
+    let blank = text.consume_blank();
+    if blank.is_empty() && ! text.starts_with(";") && ! text.starts_with("\n") {
+        text.rewind(backup);
+        return None;
+    }
+    consumed += &blank;
+    *ans_text += &consumed;
+
+    let mut values = vec![];
+    while let Some(w) = Word::parse(text, conf, false) {
+        *ans_text += &w.text.clone();
+        values.push(w);
+        *ans_text += &text.consume_blank();
+    }
+    Some(values)
+}
+
+/* do script done of for, arithmetic for and select */
+pub fn parse_do_done(text: &mut Feeder, conf: &mut ShellCore,
+                     ans_text: &mut String, my_type: &CommandType) -> Option<Script> {
+    *ans_text += &text.request_next_line(conf);
+
+    if text.starts_with( "do"){
+        *ans_text += &text.consume(2);
+    }else{
+        return None;
+    }
+
+    *ans_text += &text.request_next_line(conf);
+
+    let doing = Script::parse(text, conf, my_type)?;
+    *ans_text += &doing.text;
+
+    *ans_text += &text.request_next_line(conf);
+
+    if text.starts_with( "done"){
+        *ans_text += &text.consume(4);
+    }else{
+        return None;
+    }
+
+    Some(doing)
+}
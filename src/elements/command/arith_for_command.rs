@@ -0,0 +1,184 @@
+//SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::elements::command;
+use crate::elements::command::Command;
+use std::os::unix::prelude::RawFd;
+use crate::elements::script::Script;
+use crate::elements::redirect::Redirect;
+use crate::elements::command::CommandType;
+use crate::operators::ControlOperator;
+use crate::calculator::calculate;
+use nix::unistd::Pid;
+use crate::file_descs::*;
+
+/* for (( init ; cond ; step )) ; do script ; done */
+pub struct CommandArithFor {
+    pub init: String,
+    pub cond: String,
+    pub step: String,
+    pub doing: Option<Script>,
+    text: String,
+    pid: Option<Pid>,
+    fds: FileDescs,
+    my_type: CommandType,
+    session_leader: bool,
+}
+
+impl Command for CommandArithFor {
+    fn get_pid(&self) -> Option<Pid> { self.pid }
+    fn set_pid(&mut self, pid: Pid) { self.pid = Some(pid); }
+    fn set_session_leader(&mut self) { self.session_leader = true; }
+    fn no_connection(&self) -> bool { self.fds.no_connection() && ! self.session_leader }
+
+    fn set_pipe(&mut self, pin: RawFd, pout: RawFd, pprev: RawFd) {
+        self.fds.pipein = pin;
+        self.fds.pipeout = pout;
+        self.fds.prevpipein = pprev;
+    }
+
+    fn set_child_io(&mut self, conf: &mut ShellCore) -> Result<(), String> {
+        self.fds.set_child_io(conf)
+    }
+
+    fn get_pipe_end(&mut self) -> RawFd { self.fds.pipein }
+    fn get_pipe_out(&mut self) -> RawFd { self.fds.pipeout }
+    fn get_text(&self) -> String { self.text.clone() }
+
+    fn exec_elems(&mut self, conf: &mut ShellCore) {
+        conf.set_var("?", "0");
+        if let Err(msg) = self.run_loop(conf) {
+            eprintln!("bash: {}", msg);
+            conf.set_var("?", "1");
+        }
+    }
+}
+
+impl CommandArithFor {
+    pub fn new() -> CommandArithFor{
+        CommandArithFor {
+            init: String::new(),
+            cond: String::new(),
+            step: String::new(),
+            doing: None,
+            text: "".to_string(),
+            fds: FileDescs::new(),
+            pid: None,
+            my_type: CommandType::For,
+            session_leader: false,
+        }
+    }
+
+    fn run_loop(&mut self, conf: &mut ShellCore) -> Result<(), String> {
+        CommandArithFor::eval_expr(&self.init, conf)?;
+
+        conf.loop_level += 1;
+        let result = loop {
+            /* an empty condition is always true */
+            match CommandArithFor::eval_expr(&self.cond, conf) {
+                Ok(Some(v)) if v == "0" => break Ok(()),
+                Err(msg) => break Err(msg),
+                _ => {},
+            }
+
+            if let Some(doing) = &mut self.doing {
+                doing.exec(conf);
+            }
+            if conf.check_loop_exit() {
+                break Ok(());
+            }
+
+            if let Err(msg) = CommandArithFor::eval_expr(&self.step, conf) {
+                break Err(msg);
+            }
+        };
+        conf.loop_level -= 1;
+        result
+    }
+
+    fn eval_expr(expr: &str, conf: &mut ShellCore) -> Result<Option<String>, String> {
+        if expr.trim().is_empty() {
+            return Ok(None);
+        }
+
+        calculate(expr.to_string(), conf).map(Some)
+    }
+
+    fn parse_exprs(text: &mut Feeder, conf: &mut ShellCore, ans: &mut CommandArithFor) -> bool {
+        ans.text += &text.consume(2);
+
+        let pos = loop {
+            if let Some(p) = text.scanner_double_paren_end() {
+                break p;
+            }
+            if ! text.feed_additional_line(conf) {
+                return false;
+            }
+        };
+
+        let inside = text.consume(pos);
+        ans.text += &inside;
+        ans.text += &text.consume(2);
+
+        let exprs = inside.split(';').map(|s| s.to_string()).collect::<Vec<String>>();
+        if exprs.len() != 3 {
+            eprintln!("bash: syntax error: `(({}))'", inside);
+            return false;
+        }
+
+        ans.init = exprs[0].clone();
+        ans.cond = exprs[1].clone();
+        ans.step = exprs[2].clone();
+        true
+    }
+
+    pub fn parse(text: &mut Feeder, conf: &mut ShellCore) -> Option<CommandArithFor> {
+        if text.len() < 3 || ! text.starts_with( "for") {
+            return None;
+        }
+
+        let backup = text.clone();
+
+        let mut ans = CommandArithFor::new();
+        ans.text += &text.consume(3);
+        ans.text += &text.consume_blank();
+
+        if ! text.starts_with("((") {
+            text.rewind(backup);
+            return None;
+        }
+
+        if ! CommandArithFor::parse_exprs(text, conf, &mut ans) {
+            text.consume(text.len());
+            return None;
+        }
+
+        ans.text += &text.consume_blank();
+        let (n, op) = text.scanner_control_op();
+        if op == Some(ControlOperator::Semicolon) || op == Some(ControlOperator::NewLine) {
+            ans.text += &text.consume(n);
+        }
+
+        ans.doing = match command::parse_do_done(text, conf, &mut ans.text, &ans.my_type) {
+            Some(doing) => Some(doing),
+            None => {
+                text.rewind(backup);
+                return None;
+            },
+        };
+
+        loop {
+            ans.text += &text.consume_blank();
+
+            if let Some(r) = Redirect::parse(text, conf){
+                    ans.text += &r.text;
+                    ans.fds.redirects.push(Box::new(r));
+            }else{
+                break;
+            }
+        }
+
+        Some(ans)
+    }
+}
@@ -4,20 +4,20 @@
 use crate::{ShellCore, Feeder};
 use crate::elements::command::Command;
 use std::os::unix::prelude::RawFd;
-//use crate::operators::ControlOperator;
+use crate::operators::ControlOperator;
 use crate::elements::script::Script;
 use crate::elements::redirect::Redirect;
 use nix::unistd::Pid;
 use crate::file_descs::*;
 //use crate::feeder::scanner::*;
 use crate::elements::word::Word;
-use crate::bash_glob::glob_match;
+use crate::bash_glob::{glob_match, GLOB_SPECIALS};
 // use crate::elements::CommandElem;
 use crate::elements::command::CommandType;
 
 pub struct CommandCase {
     pub word: Word,
-    pub conddo: Vec<(Vec<String>, Option<Script>)>,
+    pub conddo: Vec<(Vec<Word>, Option<Script>, ControlOperator)>,
     text: String,
     pid: Option<Pid>,
     fds: FileDescs,
@@ -46,23 +46,9 @@ impl Command for CommandCase {
     fn get_text(&self) -> String { self.text.clone() }
 
     fn exec_elems(&mut self, conf: &mut ShellCore) {
-        let word_str = self.word.eval(conf).join(" ");
-
-        for (cond, doing) in &mut self.conddo {
-            let mut flag = false;
-            for c in cond {
-                if glob_match(c, &word_str) {
-                    if let Some(d) = doing {
-                        d.exec(conf);
-                    }
-                    flag = true;
-                    break;
-                }
-            }
-            if flag {
-                break;
-            }
-        }
+        let proc_subs = conf.proc_subs.len();
+        self.exec_clauses(conf);
+        conf.close_process_substitutions(proc_subs);
     }
 }
 
@@ -79,31 +65,77 @@ impl CommandCase {
         }
     }
 
+    fn exec_clauses(&mut self, conf: &mut ShellCore) {
+        let word_str = self.word.eval_string(conf);
+        conf.set_var("?", "0");
 
-    fn parse_cond_do_pair(text: &mut Feeder, conf: &mut ShellCore, ans: &mut CommandCase) -> bool {
+        let mut fall_through = false;
+        for (conds, doing, op) in &mut self.conddo {
+            if ! fall_through && ! conds.iter_mut()
+                    .any(|c| glob_match(&c.eval_pattern(conf, GLOB_SPECIALS), &word_str)) {
+                continue;
+            }
+
+            if let Some(d) = doing {
+                d.exec(conf);
+            }
+            if conf.break_counter > 0 || conf.continue_counter > 0 {
+                break;
+            }
+
+            match op {
+                ControlOperator::SemiAnd     => fall_through = true,  /* ;& */
+                ControlOperator::SemiSemiAnd => fall_through = false, /* ;;& */
+                _ => break,
+            }
+        }
+    }
+
+    /* ;; ;& or ;;& at the end of a clause */
+    fn scanner_clause_end(text: &mut Feeder) -> Option<(usize, ControlOperator)> {
+        match text.scanner_control_op().1 {
+            Some(ControlOperator::DoubleSemicolon) => Some((2, ControlOperator::DoubleSemicolon)),
+            Some(ControlOperator::SemiAnd)         => Some((2, ControlOperator::SemiAnd)),
+            Some(ControlOperator::SemiSemiAnd)     => Some((3, ControlOperator::SemiSemiAnd)),
+            _ => None,
+        }
+    }
+
+    fn parse_patterns(text: &mut Feeder, conf: &mut ShellCore, ans: &mut CommandCase) -> Option<Vec<Word>> {
         let mut conds = vec![];
-        ans.text += &text.request_next_line(conf);
+        if text.starts_with("(") {
+            ans.text += &text.consume(1);
+        }
 
         loop {
-            let pos = text.scanner_until_escape("|)");
-            if pos == 0 || pos == text.len() {
-                return false;
-            }
-            conds.push(text.consume(pos));
-            ans.text += &conds.last().unwrap().clone();
+            ans.text += &text.consume_blank();
+            let w = Word::parse(text, conf, false)?;
+            ans.text += &w.text;
+            conds.push(w);
+            ans.text += &text.consume_blank();
 
             if text.starts_with(")") {
-           // if text.nth(0) == ')' {
-                break;
-            }else{
                 ans.text += &text.consume(1);
+                return Some(conds);
+            }else if text.starts_with("|") {
+                ans.text += &text.consume(1);
+            }else{
+                return None;
             }
         }
+    }
 
-        ans.text += &text.consume(1);
+    fn parse_cond_do_pair(text: &mut Feeder, conf: &mut ShellCore, ans: &mut CommandCase) -> bool {
         ans.text += &text.request_next_line(conf);
 
-        let doing = if text.len() >= 2 && text.starts_with( ";;") {
+        let conds = match CommandCase::parse_patterns(text, conf, ans) {
+            Some(c) => c,
+            None    => return false,
+        };
+
+        ans.text += &text.request_next_line(conf);
+
+        let doing = if CommandCase::scanner_clause_end(text).is_some() {
             None
         }else if let Some(s) = Script::parse(text, conf, &ans.my_type) {
             ans.text += &s.text;
@@ -114,11 +146,15 @@ impl CommandCase {
 
         ans.text += &text.request_next_line(conf);
 
-        if text.len() >= 2 && text.starts_with( ";;") {
-            ans.text += &text.consume(2);
-        }
+        let op = match CommandCase::scanner_clause_end(text) {
+            Some((n, op)) => {
+                ans.text += &text.consume(n);
+                op
+            },
+            None => ControlOperator::DoubleSemicolon,
+        };
 
-        ans.conddo.push( (conds, doing) );
+        ans.conddo.push( (conds, doing, op) );
         true
     }
 
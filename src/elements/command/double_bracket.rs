@@ -0,0 +1,125 @@
+//SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::elements::command::Command;
+use nix::unistd::Pid;
+use std::os::unix::prelude::RawFd;
+use crate::elements::redirect::Redirect;
+use crate::elements::cond_expr::CondExpr;
+use crate::file_descs::*;
+
+/* [[ conditional expression ]] */
+pub struct CommandDoubleBracket {
+    text: String,
+    expr: Option<CondExpr>,
+    pid: Option<Pid>,
+    fds: FileDescs,
+    session_leader: bool,
+}
+
+impl Command for CommandDoubleBracket {
+    fn exec_elems(&mut self, conf: &mut ShellCore) {
+        let status = match &mut self.expr {
+            Some(e) => match e.eval(conf) {
+                Ok(true)  => 0,
+                Ok(false) => 1,
+                Err(msg)  => {
+                    eprintln!("bash: {}", msg);
+                    2
+                },
+            },
+            None => 2, /* a syntax error found in parsing */
+        };
+
+        conf.set_var("?", &status.to_string());
+    }
+
+    fn set_pid(&mut self, pid: Pid) { self.pid = Some(pid); }
+    fn set_session_leader(&mut self) { self.session_leader = true; }
+    fn no_connection(&self) -> bool { self.fds.no_connection() && ! self.session_leader }
+
+    fn set_child_io(&mut self, conf: &mut ShellCore) -> Result<(), String> {
+        self.fds.set_child_io(conf)
+    }
+
+    fn get_pid(&self) -> Option<Pid> { self.pid }
+
+    fn set_pipe(&mut self, pin: RawFd, pout: RawFd, pprev: RawFd) {
+        self.fds.pipein = pin;
+        self.fds.pipeout = pout;
+        self.fds.prevpipein = pprev;
+    }
+
+    fn get_pipe_end(&mut self) -> RawFd { self.fds.pipein }
+    fn get_pipe_out(&mut self) -> RawFd { self.fds.pipeout }
+    fn get_text(&self) -> String { self.text.clone() }
+}
+
+impl CommandDoubleBracket {
+    pub fn new() -> CommandDoubleBracket{
+        CommandDoubleBracket {
+            text: "".to_string(),
+            expr: None,
+            pid: None,
+            fds: FileDescs::new(),
+            session_leader: false,
+        }
+    }
+
+    /* the rest of the line is discarded and the command fails with 2 */
+    fn syntax_error(mut self, text: &mut Feeder, msg: &str) -> CommandDoubleBracket {
+        eprintln!("bash: {}", msg);
+        self.text += &text.consume(text.len());
+        self.expr = None;
+        self
+    }
+
+    pub fn parse(text: &mut Feeder, conf: &mut ShellCore) -> Option<CommandDoubleBracket> {
+        if ! text.starts_with("[[") {
+            return None;
+        }
+
+        let backup = text.clone();
+        let mut ans = CommandDoubleBracket::new();
+        ans.text = text.consume(2);
+
+        let blank = text.consume_blank();
+        if blank.is_empty() && ! text.starts_with("\n") {
+            text.rewind(backup);
+            return None;
+        }
+        ans.text += &blank;
+
+        let mut tokens = match CondExpr::parse_tokens(text, conf) {
+            Ok(Some((tokens, t))) => {
+                ans.text += &t;
+                tokens
+            },
+            Ok(None) => {
+                text.rewind(backup);
+                return None;
+            },
+            Err(msg) => return Some(ans.syntax_error(text, &msg)),
+        };
+        ans.text += &text.consume(2);
+
+        match CondExpr::parse(&mut tokens) {
+            Ok(e) => ans.expr = Some(e),
+            Err(msg) => return Some(ans.syntax_error(text, &msg)),
+        }
+
+        loop {
+            ans.text += &text.consume_blank();
+
+            if let Some(r) = Redirect::parse(text, conf){
+                ans.text += &r.text;
+                ans.fds.redirects.push(Box::new(r));
+            }else{
+                break;
+            }
+        }
+
+        Some(ans)
+    }
+}
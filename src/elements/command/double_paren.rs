@@ -21,20 +21,37 @@ pub struct CommandDoubleParen {
 }
 
 impl Command for CommandDoubleParen {
-    fn exec(&mut self, conf: &mut ShellCore) {
-        self.substitution_text = calculate(self.expression.clone(), conf);
+    fn exec_elems(&mut self, conf: &mut ShellCore) {
+        match calculate(self.expression.clone(), conf) {
+            Ok(v) => self.substitution_text = v,
+            Err(msg) => {
+                match self.substitution {
+                    true  => eprintln!("bash: {}", msg),
+                    false => eprintln!("bash: ((: {}", msg),
+                }
+                self.substitution_text = String::new();
+                conf.set_var("?", "1");
+                conf.eval_error = self.substitution;
+                return;
+            },
+        }
+
+        /* $(( )) doesn't change the exit status */
+        if ! self.substitution {
+            let status = if self.substitution_text == "0" { "1" } else { "0" };
+            conf.set_var("?", status);
+        }
+    }
 
-        let status = if self.substitution_text == "0" {
-            "1"
-        }else{
-            "0"
-        }.to_string();
+    fn set_pid(&mut self, pid: Pid) { self.pid = Some(pid); }
+    fn set_session_leader(&mut self) { self.session_leader = true; }
+    fn no_connection(&self) -> bool { self.fds.no_connection() && ! self.session_leader }
 
-        conf.set_var("?", &status);
+    fn set_child_io(&mut self, conf: &mut ShellCore) -> Result<(), String> {
+        self.fds.set_child_io(conf)
     }
 
     fn get_pid(&self) -> Option<Pid> { self.pid }
-    fn set_session_leader(&mut self) { self.session_leader = true; }
 
     fn set_pipe(&mut self, pin: RawFd, pout: RawFd, pprev: RawFd) {
         self.fds.pipein = pin;
@@ -74,32 +91,26 @@ impl CommandDoubleParen {
         loop{
             ans.text = text.consume(2);
 
-            let pos = text.scanner_until(0, ")");
-
-            if pos != text.len() {
+            if let Some(pos) = text.scanner_double_paren_end() {
                 ans.expression = text.consume(pos);
                 ans.text += &ans.expression.clone();
-            }else{
-                (backup, input_success) = text.rewind_feed_backup(&backup, conf);
-                if ! input_success {
-                    text.consume(text.len());
-                    return None;
-                }
-                continue;
+                break;
             }
 
-            if /*text.len() < 2 ||*/ ! text.starts_with( "))") {
-                (backup, input_success) = text.rewind_feed_backup(&backup, conf);
-                if ! input_success {
-                    text.consume(text.len());
-                    return None;
-                }
-            }else{
-                break;
+            /* such as ((echo a) ), which is a subshell in a subshell */
+            if text.scanner_single_paren_end() {
+                text.rewind(backup);
+                return None;
+            }
+
+            (backup, input_success) = text.rewind_feed_backup(&backup, conf);
+            if ! input_success {
+                text.consume(text.len());
+                return None;
             }
         }
 
-        text.consume(2);
+        ans.text += &text.consume(2);
         if substitution {
             return Some(ans);
         }
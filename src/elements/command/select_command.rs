@@ -0,0 +1,198 @@
+//SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::elements::command;
+use crate::elements::command::Command;
+use std::io;
+use std::io::Write;
+use std::os::unix::prelude::RawFd;
+use crate::elements::script::Script;
+use crate::elements::redirect::Redirect;
+use crate::elements::command::CommandType;
+use crate::elements::word::Word;
+use crate::operators::ControlOperator;
+use nix::unistd;
+use nix::unistd::Pid;
+use crate::file_descs::*;
+
+/* select name in words ; do script ; done */
+pub struct CommandSelect {
+    pub name: String,
+    pub values: Vec<Word>,
+    pub has_in: bool,
+    pub doing: Option<Script>,
+    text: String,
+    pid: Option<Pid>,
+    fds: FileDescs,
+    my_type: CommandType,
+    session_leader: bool,
+}
+
+impl Command for CommandSelect {
+    fn get_pid(&self) -> Option<Pid> { self.pid }
+    fn set_pid(&mut self, pid: Pid) { self.pid = Some(pid); }
+    fn set_session_leader(&mut self) { self.session_leader = true; }
+    fn no_connection(&self) -> bool { self.fds.no_connection() && ! self.session_leader }
+
+    fn set_pipe(&mut self, pin: RawFd, pout: RawFd, pprev: RawFd) {
+        self.fds.pipein = pin;
+        self.fds.pipeout = pout;
+        self.fds.prevpipein = pprev;
+    }
+
+    fn set_child_io(&mut self, conf: &mut ShellCore) -> Result<(), String> {
+        self.fds.set_child_io(conf)
+    }
+
+    fn get_pipe_end(&mut self) -> RawFd { self.fds.pipein }
+    fn get_pipe_out(&mut self) -> RawFd { self.fds.pipeout }
+    fn get_text(&self) -> String { self.text.clone() }
+
+    fn exec_elems(&mut self, conf: &mut ShellCore) {
+        let proc_subs = conf.proc_subs.len();
+        self.exec_loop(conf);
+        conf.close_process_substitutions(proc_subs);
+    }
+}
+
+impl CommandSelect {
+    pub fn new() -> CommandSelect{
+        CommandSelect {
+            name: String::new(),
+            values: vec![],
+            has_in: false,
+            doing: None,
+            text: "".to_string(),
+            fds: FileDescs::new(),
+            pid: None,
+            my_type: CommandType::Select,
+            session_leader: false,
+        }
+    }
+
+    fn exec_loop(&mut self, conf: &mut ShellCore) {
+        let values = if self.has_in {
+            command::eval_words(&mut self.values, conf)
+        }else{
+            conf.args[1..].to_vec()
+        };
+
+        if values.is_empty() {
+            conf.set_var("?", "0");
+            return;
+        }
+
+        CommandSelect::print_menu(&values);
+        conf.loop_level += 1;
+        loop {
+            let line = match CommandSelect::read_reply(conf) {
+                Some(ln) => ln,
+                None => { /* EOF */
+                    eprintln!();
+                    conf.set_var("?", "1");
+                    break;
+                },
+            };
+
+            conf.set_var("REPLY", &line);
+            if line.is_empty() {
+                CommandSelect::print_menu(&values);
+                continue;
+            }
+
+            let value = match line.trim().parse::<usize>() {
+                Ok(n) if n >= 1 && n <= values.len() => values[n-1].clone(),
+                _ => String::new(),
+            };
+            conf.set_var(&self.name, &value);
+
+            if let Some(doing) = &mut self.doing {
+                doing.exec(conf);
+            }
+            if conf.check_loop_exit() {
+                break;
+            }
+        }
+        conf.loop_level -= 1;
+    }
+
+    fn print_menu(values: &[String]) {
+        let width = values.len().to_string().len();
+        for (i, v) in values.iter().enumerate() {
+            eprintln!("{:>width$}) {}", i+1, v, width = width);
+        }
+    }
+
+    fn read_reply(conf: &mut ShellCore) -> Option<String> {
+        let ps3 = conf.find_var("PS3").unwrap_or("#? ".to_string());
+        eprint!("{}", ps3);
+        io::stderr().flush().expect("Cannot flush stderr");
+
+        /* byte by byte not to consume the input after the line */
+        let mut line = vec![];
+        let mut ch = [0; 1];
+        loop {
+            match unistd::read(0, &mut ch) {
+                Ok(1) if ch[0] != b'\n' => line.push(ch[0]),
+                Ok(1) => break,
+                _ if line.is_empty() => return None,
+                _ => break,
+            }
+        }
+        Some(String::from_utf8_lossy(&line).to_string())
+    }
+
+    pub fn parse(text: &mut Feeder, conf: &mut ShellCore) -> Option<CommandSelect> {
+        if text.len() < 6 || ! text.starts_with( "select") {
+            return None;
+        }
+
+        let backup = text.clone();
+
+        let mut ans = CommandSelect::new();
+        ans.text += &text.consume(6);
+
+        let blank = text.consume_blank();
+        let pos = text.scanner_name(0);
+        if blank.is_empty() || pos == 0 {
+            text.rewind(backup);
+            return None;
+        }
+        ans.text += &blank;
+        ans.name = text.consume(pos);
+        ans.text += &ans.name.clone();
+        ans.text += &text.consume_blank_return();
+
+        if let Some(values) = command::parse_in_words(text, conf, &mut ans.text) {
+            ans.values = values;
+            ans.has_in = true;
+        }
+
+        let (n, op) = text.scanner_control_op();
+        if op == Some(ControlOperator::Semicolon) || op == Some(ControlOperator::NewLine) {
+            ans.text += &text.consume(n);
+        }
+
+        ans.doing = match command::parse_do_done(text, conf, &mut ans.text, &ans.my_type) {
+            Some(doing) => Some(doing),
+            None => {
+                text.rewind(backup);
+                return None;
+            },
+        };
+
+        loop {
+            ans.text += &text.consume_blank();
+
+            if let Some(r) = Redirect::parse(text, conf){
+                    ans.text += &r.text;
+                    ans.fds.redirects.push(Box::new(r));
+            }else{
+                break;
+            }
+        }
+
+        Some(ans)
+    }
+}
@@ -23,6 +23,7 @@ use crate::file_descs::*;
 /* command: delim word delim word delim word ... eoc */
 pub struct SimpleCommand {
     vars: Vec<Substitution>,
+    declare_vars: Vec<Substitution>,
     pub args: Vec<Word>,
     pub text: String,
     pub pid: Option<Pid>,
@@ -37,6 +38,40 @@ fn is_reserve(s: &String) -> bool {
 
 impl Command for SimpleCommand {
     fn exec(&mut self, core: &mut ShellCore) {
+        let proc_subs = core.proc_subs.len();
+        self.exec_command(core);
+        core.close_process_substitutions(proc_subs);
+    }
+
+    fn set_pipe(&mut self, pin: RawFd, pout: RawFd, pprev: RawFd) {
+        self.fds.pipein = pin;
+        self.fds.pipeout = pout;
+        self.fds.prevpipein = pprev;
+    }
+
+    fn set_session_leader(&mut self) { self.session_leader = true; }
+
+    fn get_pid(&self) -> Option<Pid> { self.pid }
+    fn get_pipe_end(&mut self) -> RawFd { self.fds.pipein }
+    fn get_pipe_out(&mut self) -> RawFd { self.fds.pipeout }
+    fn get_text(&self) -> String { self.text.clone() }
+}
+
+impl SimpleCommand {
+    pub fn new() -> SimpleCommand{
+        SimpleCommand {
+            vars: vec![],
+            declare_vars: vec![],
+            args: vec![],
+            //eoc: None,
+            text: "".to_string(),
+            pid: None,
+            fds: FileDescs::new(),
+            session_leader: false,
+        }
+    }
+
+    fn exec_command(&mut self, core: &mut ShellCore) {
         if self.args.len() == 0 {
             self.set_vars(core);
             return;
@@ -46,7 +81,17 @@ impl Command for SimpleCommand {
             eprintln!("{}", self.text.trim_end());
         }
 
+        core.eval_error = false;
         let mut args = self.eval(core);
+        if core.eval_error {
+            core.eval_error = false;
+            core.set_var("?", "1");
+            return;
+        }
+        if args.is_empty() {
+            self.set_vars(core);
+            return;
+        }
         //eprintln!("NUM:{} {:?}", args.len(), &args); 
         core.set_var("_", &args[args.len()-1]);
 
@@ -84,36 +129,9 @@ impl Command for SimpleCommand {
         }
     }
 
-    fn set_pipe(&mut self, pin: RawFd, pout: RawFd, pprev: RawFd) {
-        self.fds.pipein = pin;
-        self.fds.pipeout = pout;
-        self.fds.prevpipein = pprev;
-    }
-
-    fn set_session_leader(&mut self) { self.session_leader = true; }
-
-    fn get_pid(&self) -> Option<Pid> { self.pid }
-    fn get_pipe_end(&mut self) -> RawFd { self.fds.pipein }
-    fn get_pipe_out(&mut self) -> RawFd { self.fds.pipeout }
-    fn get_text(&self) -> String { self.text.clone() }
-}
-
-impl SimpleCommand {
-    pub fn new() -> SimpleCommand{
-        SimpleCommand {
-            vars: vec![],
-            args: vec![],
-            //eoc: None,
-            text: "".to_string(),
-            pid: None,
-            fds: FileDescs::new(),
-            session_leader: false,
-        }
-    }
-
     fn run_on_this_process(&mut self, args: &mut Vec<String>, core: &mut ShellCore) -> bool {
         if let Some(func) = core.get_builtin(&args[0]) {
-            let status = func(core, args);
+            let status = self.exec_builtin(func, args, core);
             core.set_var("?", &status.to_string());
             true
         }else{
@@ -121,18 +139,33 @@ impl SimpleCommand {
         }
     }
 
+    /* the assignments given to declare are set after the attributes */
+    fn exec_builtin(&mut self, func: fn(&mut ShellCore, &mut Vec<String>) -> i32,
+                    args: &mut Vec<String>, core: &mut ShellCore) -> i32 {
+        let mut status = func(core, args);
+        if status != 0 {
+            return status;
+        }
+
+        for s in &mut self.declare_vars {
+            if let Err(msg) = s.set(core) {
+                eprintln!("bash: {}: {}", args[0], msg);
+                status = 1;
+            }
+        }
+        status
+    }
+
     fn eval(&mut self, core: &mut ShellCore) -> Vec<String> {
         let mut args = vec![];
 
         for word in &mut self.args {
             for s in &word.eval(core) {
-                args.append(&mut eval_glob(&s.clone()));
+                args.append(&mut eval_glob(s));
             }
         };
 
-        args.iter()
-            .map(|a| Word::remove_escape(&a))
-            .collect()
+        args
     }
 
     pub fn push_vars(&mut self, s: Substitution){
@@ -155,12 +188,15 @@ impl SimpleCommand {
         let mut feeder = Feeder::new_from(text);
         if let Some(mut f) = command::parse(&mut feeder, core) {
             let backup = core.args.clone();
+            let loop_level = core.loop_level;
             core.args = args.to_vec();
             core.return_enable = true;
+            core.loop_level = 0;
             f.exec(core);
             self.pid = f.get_pid();
             core.args = backup;
             core.return_enable = false;
+            core.loop_level = loop_level;
         }else{
             panic!("Shell internal error on function");
         };
@@ -173,7 +209,7 @@ impl SimpleCommand {
         }
 
         if let Some(func) = core.get_builtin(&args[0]) {
-            exit(func(core, args));
+            exit(self.exec_builtin(func, args, core));
         }
 
         //let fullpath = get_fullpath(&args[0]);
@@ -191,7 +227,7 @@ impl SimpleCommand {
 
         for v in &mut self.vars {
             let key = (*v).name.clone();
-            let value =  (*v).value.eval(core).join(" ");
+            let value =  (*v).value.eval(core);
             env::set_var(key, value);
         }
         env::set_var("_".to_string(), args[0].clone());
@@ -242,6 +278,13 @@ impl SimpleCommand {
         ! is_reserve(text)
     }
 
+    fn parse_declare_var(text: &mut Feeder, core: &mut ShellCore, ans: &SimpleCommand) -> Option<Substitution> {
+        match ans.args.first() {
+            Some(w) if w.text == "declare" || w.text == "typeset" => Substitution::parse(text, core),
+            _ => None,
+        }
+    }
+
     fn args_and_redirects(text: &mut Feeder, core: &mut ShellCore, ans: &mut SimpleCommand) -> bool {
         let mut ok = false;
         loop {
@@ -249,6 +292,11 @@ impl SimpleCommand {
             if let Some(r) = Redirect::parse(text, core){
                 ans.text += &r.text;
                 ans.fds.redirects.push(Box::new(r));
+            }else if let Some(s) = Self::parse_declare_var(text, core, ans) {
+                ans.text += &s.text;
+                let name = Word::parse(&mut Feeder::new_from(s.name.clone()), core, false);
+                ans.args.push(name.unwrap());
+                ans.declare_vars.push(s);
             }else if let Some(a) = Word::parse(text, core, false) {
                 if ! SimpleCommand::ng_check(&a.text, ans.args.len() == 0){
                     text.rewind(backup);
@@ -310,13 +358,10 @@ impl SimpleCommand {
 
     fn set_vars(&mut self, core: &mut ShellCore){
         for e in &mut self.vars {
-            let sub = e.eval(core);
-            let (key, value) = (sub[0].clone(), sub[1].clone());
-            if let Ok(_) = env::var(&key) {
-                env::set_var(key, value);
-            }else{
-                core.set_var(&key, &value);
-            };
+            if let Err(msg) = e.set(core) {
+                eprintln!("bash: {}", msg);
+                core.set_var("?", "1");
+            }
         };
     }
 }
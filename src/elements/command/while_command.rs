@@ -10,9 +10,11 @@ use crate::elements::command::CommandType;
 use nix::unistd::Pid;
 use crate::file_descs::*;
 
-/* ( script ) */
+/* while script ; do script ; done
+   until script ; do script ; done */
 pub struct CommandWhile {
     pub conddo: Option<(Script, Script)>,
+    pub is_until: bool,
     text: String,
     pid: Option<Pid>,
     fds: FileDescs,
@@ -24,7 +26,7 @@ impl Command for CommandWhile {
     fn get_pid(&self) -> Option<Pid> { self.pid }
     fn set_pid(&mut self, pid: Pid) { self.pid = Some(pid); }
     fn set_session_leader(&mut self) { self.session_leader = true; }
-    fn no_connection(&self) -> bool { self.fds.no_connection() }
+    fn no_connection(&self) -> bool { self.fds.no_connection() && ! self.session_leader }
 
     fn set_pipe(&mut self, pin: RawFd, pout: RawFd, pprev: RawFd) {
         self.fds.pipein = pin;
@@ -41,16 +43,24 @@ impl Command for CommandWhile {
     fn get_text(&self) -> String { self.text.clone() }
 
     fn exec_elems(&mut self, conf: &mut ShellCore) {
+        conf.loop_level += 1;
         loop {
             if let Some((cond, doing)) = &mut self.conddo {
                 cond.exec(conf);
-                if conf.vars["?"] != "0" {
+                if conf.check_loop_exit() {
+                    break;
+                }
+                if (conf.vars["?"] != "0") != self.is_until {
                     conf.set_var("?", "0");
                     break;
                 }
                 doing.exec(conf);
+                if conf.check_loop_exit() {
+                    break;
+                }
             }
         }
+        conf.loop_level -= 1;
     }
 }
 
@@ -58,6 +68,7 @@ impl CommandWhile {
     pub fn new() -> CommandWhile{
         CommandWhile {
             conddo: None,
+            is_until: false,
             text: "".to_string(),
             fds: FileDescs::new(),
             pid: None,
@@ -99,13 +110,14 @@ impl CommandWhile {
     }
 
     pub fn parse(text: &mut Feeder, conf: &mut ShellCore) -> Option<CommandWhile> {
-        if text.len() < 5 || ! text.starts_with( "while") {
+        if text.len() < 5 || ! (text.starts_with( "while") || text.starts_with( "until")) {
             return None;
         }
 
         let backup = text.clone();
 
         let mut ans = CommandWhile::new();
+        ans.is_until = text.starts_with( "until");
         ans.text += &text.consume(5);
 
         if ! CommandWhile::parse_cond_do_pair(text, conf, &mut ans) {
@@ -0,0 +1,397 @@
+//SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use std::collections::VecDeque;
+use std::fs;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use nix::unistd::{access, AccessFlags, isatty, geteuid, getegid};
+
+use crate::{ShellCore, Feeder};
+use crate::bash_glob::{glob_match, GLOB_SPECIALS};
+use crate::bash_regex::regex_match;
+use crate::calculator::evaluate;
+use crate::debuginfo::DebugInfo;
+use crate::elements::subscript::Subscript;
+use crate::elements::subword;
+use crate::elements::subword::string_non_quoted::SubwordStringNonQuoted;
+use crate::elements::word::Word;
+
+const UNARY_OPS: [&str; 24] = [
+    "-a", "-b", "-c", "-d", "-e", "-f", "-g", "-h", "-k", "-n", "-p", "-r",
+    "-s", "-t", "-u", "-v", "-w", "-x", "-z", "-G", "-L", "-N", "-O", "-S",
+];
+
+const BINARY_OPS: [&str; 15] = [
+    "==", "=", "!=", "=~", "<", ">", "-eq", "-ne", "-lt", "-le", "-gt", "-ge",
+    "-nt", "-ot", "-ef",
+];
+
+/* a word or an operator between [[ and ]] */
+pub struct CondToken {
+    pub text: String,
+    pub word: Option<Word>,
+}
+
+impl CondToken {
+    fn is_op(&self, op: &str) -> bool {
+        self.text == op
+    }
+
+    fn is_binary_op(&self) -> bool {
+        BINARY_OPS.iter().any(|op| self.text == *op)
+    }
+
+    fn is_unary_op(&self) -> bool {
+        self.word.is_some() && UNARY_OPS.iter().any(|op| self.text == *op)
+    }
+
+    fn is_logical_op(&self) -> bool {
+        self.word.is_none() && ( self.text == "&&" || self.text == "||" || self.text == ")" )
+    }
+}
+
+pub enum CondExpr {
+    Or(Box<CondExpr>, Box<CondExpr>),
+    And(Box<CondExpr>, Box<CondExpr>),
+    Not(Box<CondExpr>),
+    Unary(String, Word),
+    Binary(Word, String, Word),
+    Single(Word),
+}
+
+impl CondExpr {
+    pub fn eval(&mut self, core: &mut ShellCore) -> Result<bool, String> {
+        match self {
+            CondExpr::Or(left, right)  => Ok(left.eval(core)? || right.eval(core)?),
+            CondExpr::And(left, right) => Ok(left.eval(core)? && right.eval(core)?),
+            CondExpr::Not(expr)        => Ok(! expr.eval(core)?),
+            CondExpr::Single(w)        => Ok(! eval_string(w, core).is_empty()),
+            CondExpr::Unary(op, w) => {
+                let s = eval_string(w, core);
+                unary_test(op, &s, core)
+            },
+            CondExpr::Binary(left, op, right) => {
+                let lhs = eval_string(left, core);
+                match op.as_str() {
+                    "==" | "=" => Ok(glob_match(&eval_pattern(right, core), &lhs)),
+                    "!="       => Ok(! glob_match(&eval_pattern(right, core), &lhs)),
+                    "=~"       => regex_test(&lhs, &eval_regex(right, core), core),
+                    _          => binary_test(&lhs, op, &eval_string(right, core), core),
+                }
+            },
+        }
+    }
+
+    pub fn parse(tokens: &mut VecDeque<CondToken>) -> Result<CondExpr, String> {
+        let ans = CondExpr::parse_or(tokens)?;
+        if let Some(t) = tokens.front() {
+            return Err(format!("syntax error in conditional expression: unexpected token `{}'", t.text));
+        }
+        Ok(ans)
+    }
+
+    fn parse_or(tokens: &mut VecDeque<CondToken>) -> Result<CondExpr, String> {
+        let mut left = CondExpr::parse_and(tokens)?;
+        while tokens.front().is_some_and(|t| t.word.is_none() && t.is_op("||")) {
+            tokens.pop_front();
+            let right = CondExpr::parse_and(tokens)?;
+            left = CondExpr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(tokens: &mut VecDeque<CondToken>) -> Result<CondExpr, String> {
+        let mut left = CondExpr::parse_not(tokens)?;
+        while tokens.front().is_some_and(|t| t.word.is_none() && t.is_op("&&")) {
+            tokens.pop_front();
+            let right = CondExpr::parse_not(tokens)?;
+            left = CondExpr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(tokens: &mut VecDeque<CondToken>) -> Result<CondExpr, String> {
+        let is_not = match tokens.front() {
+            Some(t) => t.word.is_some() && t.is_op("!") && tokens.len() > 1,
+            None    => false,
+        };
+
+        if is_not {
+            tokens.pop_front();
+            return Ok(CondExpr::Not(Box::new(CondExpr::parse_not(tokens)?)));
+        }
+
+        CondExpr::parse_primary(tokens)
+    }
+
+    fn parse_primary(tokens: &mut VecDeque<CondToken>) -> Result<CondExpr, String> {
+        let first = match tokens.pop_front() {
+            Some(t) => t,
+            None    => return Err("unexpected argument in conditional expression".to_string()),
+        };
+
+        if first.word.is_none() {
+            if ! first.is_op("(") {
+                return Err(format!("syntax error in conditional expression: unexpected token `{}'", first.text));
+            }
+
+            let ans = CondExpr::parse_or(tokens)?;
+            match tokens.pop_front() {
+                Some(t) if t.word.is_none() && t.is_op(")") => return Ok(ans),
+                _ => return Err("expected `)'".to_string()),
+            }
+        }
+
+        let next_is_binary = tokens.front().is_some_and(|t| t.is_binary_op());
+
+        if first.is_unary_op() && ! next_is_binary {
+            let operand = match tokens.pop_front() {
+                Some(t) if ! t.is_logical_op() => t,
+                Some(t) => return Err(format!("unexpected argument `{}' to conditional unary operator", t.text)),
+                None    => return Err("unexpected argument `]]' to conditional unary operator".to_string()),
+            };
+            return match operand.word {
+                Some(w) => Ok(CondExpr::Unary(first.text, w)),
+                None    => Err(format!("unexpected argument `{}' to conditional unary operator", operand.text)),
+            };
+        }
+
+        let left = first.word.unwrap();
+        if ! next_is_binary {
+            return Ok(CondExpr::Single(left));
+        }
+
+        let op = tokens.pop_front().unwrap().text;
+        match tokens.pop_front() {
+            Some(CondToken{text: _, word: Some(right)}) => Ok(CondExpr::Binary(left, op, right)),
+            Some(t) => Err(format!("unexpected argument `{}' to conditional binary operator", t.text)),
+            None    => Err("unexpected argument to conditional binary operator".to_string()),
+        }
+    }
+
+    /* reads words and operators until ]]; Err for a syntax error which cannot be recovered */
+    pub fn parse_tokens(text: &mut Feeder, conf: &mut ShellCore) -> Result<Option<(VecDeque<CondToken>, String)>, String> {
+        let mut tokens = VecDeque::new();
+        let mut ans_text = String::new();
+
+        loop {
+            ans_text += &text.consume_blank_return();
+            if text.len() == 0 {
+                if ! text.feed_additional_line(conf) {
+                    return Ok(None);
+                }
+                continue;
+            }
+
+            if text.starts_with("]]") {
+                return Ok(Some((tokens, ans_text)));
+            }
+
+            let op_len = ["&&", "||", "(", ")", "<", ">"].iter()
+                .find(|op| text.starts_with(op))
+                .map_or(0, |op| op.len());
+
+            let after_regex_op = tokens.back().is_some_and(|t: &CondToken| t.word.is_some() && t.is_op("=~"));
+            if after_regex_op {
+                if let Some(w) = CondExpr::parse_regex_word(text, conf)? {
+                    ans_text += &w.text;
+                    tokens.push_back(CondToken{text: w.text.clone(), word: Some(w)});
+                    continue;
+                }
+            }
+
+            if op_len > 0 {
+                let op = text.consume(op_len);
+                ans_text += &op;
+                tokens.push_back(CondToken{text: op, word: None});
+            }else if let Some(w) = Word::parse(text, conf, false) {
+                ans_text += &w.text;
+                tokens.push_back(CondToken{text: w.text.clone(), word: Some(w)});
+            }else{
+                return Ok(None);
+            }
+        }
+    }
+
+    /* the right side of =~ may contain unquoted parens, | and blanks in parens */
+    fn parse_regex_word(text: &mut Feeder, conf: &mut ShellCore) -> Result<Option<Word>, String> {
+        let mut ans = Word{
+            text: String::new(),
+            pos: DebugInfo::init(text),
+            subwords: vec![],
+        };
+
+        let mut depth = 0;
+        while text.len() > 0 {
+            let ch = text.nth(0);
+            let literal_dollar = ch == '$' && (text.len() == 1 || "|) \t\n".contains(text.nth(1)));
+
+            let len = match ch {
+                '\n' => break,
+                ' ' | '\t' if depth == 0 => break,
+                '&' if depth == 0 => break,
+                ')' if depth == 0 => break,
+                ' ' | '\t' | '|' | '<' | '>' | '&' | ';' => 1,
+                '(' => { depth += 1; 1 },
+                ')' => { depth -= 1; 1 },
+                '$' if literal_dollar => 1,
+                _ => 0,
+            };
+
+            if len > 0 {
+                let s = text.consume(len);
+                ans.text += &s;
+                ans.subwords.push(Box::new(SubwordStringNonQuoted{text: s, pos: DebugInfo::init(text)}));
+            }else if let Some(sw) = subword::parse_in_arg(text, conf, false) {
+                ans.text += &sw.get_text();
+                ans.subwords.push(sw);
+            }else{
+                break;
+            }
+        }
+
+        if depth > 0 {
+            return Err("unexpected EOF while looking for matching `)'".to_string());
+        }
+
+        if ans.text.is_empty() {
+            Ok(None)
+        }else{
+            Ok(Some(ans))
+        }
+    }
+}
+
+fn eval_string(w: &mut Word, core: &mut ShellCore) -> String {
+    w.eval_string(core)
+}
+
+fn eval_pattern(w: &mut Word, core: &mut ShellCore) -> String {
+    w.eval_pattern(core, GLOB_SPECIALS)
+}
+
+fn eval_regex(w: &mut Word, core: &mut ShellCore) -> String {
+    w.eval_pattern(core, "\\.[]()*+?{}|^$")
+}
+
+fn regex_test(s: &str, pattern: &str, core: &mut ShellCore) -> Result<bool, String> {
+    let ans = regex_match(s, pattern)?;
+    let matched = ans.is_some();
+    core.set_array("BASH_REMATCH", ans.unwrap_or_default());
+    Ok(matched)
+}
+
+/* operands of arithmetic comparisons are arithmetic expressions */
+fn to_integer(s: &str, core: &mut ShellCore) -> Result<i64, String> {
+    if let Ok(n) = s.trim().parse::<i64>() {
+        return Ok(n);
+    }
+
+    evaluate(s, core)
+}
+
+/* name or name[subscript] given to -v */
+fn is_set(s: &str, core: &mut ShellCore) -> Result<bool, String> {
+    let (name, subscript) = match s.find('[') {
+        Some(p) if s.ends_with(']') => (&s[..p], &s[p..]),
+        _ => return Ok(core.vars.contains_key(s) || std::env::var(s).is_ok()
+                       || core.find_array_elem(s, 0).is_some()
+                       || core.find_assoc_elem(s, "0").is_some()),
+    };
+
+    let mut feeder = Feeder::new_from(subscript.to_string());
+    let mut sub = match Subscript::parse(&mut feeder, core) {
+        Some(sub) if feeder.len() == 0 => sub,
+        _ => return Ok(false),
+    };
+
+    if sub.is_all() {
+        return Ok(! core.get_array_values(name).is_empty());
+    }
+
+    if core.is_assoc(name) {
+        let key = sub.eval_key(core)?;
+        return Ok(core.find_assoc_elem(name, &key).is_some());
+    }
+    let index = sub.eval_index(core)?;
+    Ok(core.find_array_elem(name, index).is_some())
+}
+
+fn unary_test(op: &str, s: &str, core: &mut ShellCore) -> Result<bool, String> {
+    match op {
+        "-z" => return Ok(s.is_empty()),
+        "-n" => return Ok(! s.is_empty()),
+        "-v" => return is_set(s, core),
+        "-t" => return Ok(isatty(to_integer(s, core)? as i32).unwrap_or(false)),
+        "-h" | "-L" => return Ok(fs::symlink_metadata(s).is_ok_and(|m| m.file_type().is_symlink())),
+        "-r" => return Ok(access(s, AccessFlags::R_OK).is_ok()),
+        "-w" => return Ok(access(s, AccessFlags::W_OK).is_ok()),
+        "-x" => return Ok(access(s, AccessFlags::X_OK).is_ok()),
+        _ => {},
+    }
+
+    let meta = match fs::metadata(s) {
+        Ok(m)  => m,
+        Err(_) => return Ok(false),
+    };
+
+    let mode = meta.permissions().mode();
+    let ans = match op {
+        "-a" | "-e" => true,
+        "-b" => meta.file_type().is_block_device(),
+        "-c" => meta.file_type().is_char_device(),
+        "-d" => meta.is_dir(),
+        "-f" => meta.is_file(),
+        "-p" => meta.file_type().is_fifo(),
+        "-S" => meta.file_type().is_socket(),
+        "-s" => meta.len() > 0,
+        "-g" => mode & 0o2000 != 0,
+        "-u" => mode & 0o4000 != 0,
+        "-k" => mode & 0o1000 != 0,
+        "-O" => meta.uid() == geteuid().as_raw(),
+        "-G" => meta.gid() == getegid().as_raw(),
+        "-N" => meta.mtime() > meta.atime(),
+        _ => false,
+    };
+    Ok(ans)
+}
+
+fn file_compare(left: &str, op: &str, right: &str) -> bool {
+    let (lm, rm) = match (fs::metadata(left), fs::metadata(right)) {
+        (Ok(l), Ok(r)) => (Some(l), Some(r)),
+        (Ok(l), Err(_)) => (Some(l), None),
+        (Err(_), Ok(r)) => (None, Some(r)),
+        _ => (None, None),
+    };
+
+    let mtime = |m: &fs::Metadata| (m.mtime(), m.mtime_nsec());
+    match (op, lm, rm) {
+        ("-nt", Some(l), Some(r)) => mtime(&l) > mtime(&r),
+        ("-nt", Some(_), None)    => true,
+        ("-ot", Some(l), Some(r)) => mtime(&l) < mtime(&r),
+        ("-ot", None, Some(_))    => true,
+        ("-ef", Some(l), Some(r)) => l.dev() == r.dev() && l.ino() == r.ino(),
+        _ => false,
+    }
+}
+
+fn binary_test(left: &str, op: &str, right: &str, core: &mut ShellCore) -> Result<bool, String> {
+    let ans = match op {
+        "<" => left < right,
+        ">" => left > right,
+        "-nt" | "-ot" | "-ef" => file_compare(left, op, right),
+        _ => {
+            let (l, r) = (to_integer(left, core)?, to_integer(right, core)?);
+            match op {
+                "-eq" => l == r,
+                "-ne" => l != r,
+                "-lt" => l < r,
+                "-le" => l <= r,
+                "-gt" => l > r,
+                "-ge" => l >= r,
+                _ => return Err(format!("{}: conditional binary operator expected", op)),
+            }
+        },
+    };
+    Ok(ans)
+}
@@ -0,0 +1,142 @@
+//SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::debuginfo::DebugInfo;
+use crate::{Feeder, ShellCore};
+use crate::elements::subword::Subword;
+use crate::elements::subword::backquoted::SubwordBackquoted;
+use crate::elements::subword::command_substitution::SubwordCommandSubstitution;
+use crate::elements::subword::math_substitution::SubwordMathSubstitution;
+use crate::elements::subword::variable::SubwordVariable;
+use crate::elements::word::{Word, QuotedString};
+
+/* a part of a document which is not expanded */
+struct HereDocumentString {
+    text: String,
+}
+
+impl Subword for HereDocumentString {
+    /* a backslash only escapes $ ` \ and a newline */
+    fn eval(&mut self, _conf: &mut ShellCore, _: bool) -> Vec<Vec<QuotedString>> {
+        let mut ans = String::new();
+        let mut chars = self.text.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match (ch, chars.peek()) {
+                ('\\', Some('\n')) => {chars.next();},
+                ('\\', Some(&c)) if "$`\\".contains(c) => {
+                    ans.push(c);
+                    chars.next();
+                },
+                _ => ans.push(ch),
+            }
+        }
+        vec!(vec!(QuotedString::quoted(&ans)))
+    }
+
+    fn get_text(&self) -> String {
+        self.text.clone()
+    }
+}
+
+pub struct HereDocument {
+    pub text: String,
+    pub pos: DebugInfo,
+    pub delimiter: String,
+    pub quoted: bool,
+    subwords: Vec<Box<dyn Subword>>,
+}
+
+impl HereDocument {
+    pub fn eval(&mut self, conf: &mut ShellCore) -> String {
+        if self.quoted {
+            return self.text.clone();
+        }
+
+        let in_double_quot = conf.in_double_quot;
+        conf.in_double_quot = true;
+
+        let mut ans = String::new();
+        for sw in &mut self.subwords {
+            if let Some(fields) = sw.eval(conf, false).first() {
+                ans += &QuotedString::join(fields, " ").to_string();
+            }
+        }
+
+        conf.in_double_quot = in_double_quot;
+        ans
+    }
+
+    /* quote removal of the word after << */
+    fn delimiter(word: &str) -> (String, bool) {
+        let mut ans = String::new();
+        let mut quoted = false;
+        let mut quote: Option<char> = None;
+        let mut chars = word.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match (quote, ch) {
+                (Some(q), c) if c == q => quote = None,
+                (Some('"'), '\\') => {
+                    match chars.peek() {
+                        Some(&c) if "$`\"\\".contains(c) => {
+                            ans.push(c);
+                            chars.next();
+                        },
+                        _ => ans.push(ch),
+                    }
+                },
+                (Some(_), c) => ans.push(c),
+                (None, '\'') | (None, '"') => {
+                    quote = Some(ch);
+                    quoted = true;
+                },
+                (None, '\\') => {
+                    quoted = true;
+                    if let Some(c) = chars.next() {
+                        ans.push(c);
+                    }
+                },
+                (None, c) => ans.push(c),
+            }
+        }
+        (ans, quoted)
+    }
+
+    fn parse_subwords(&mut self, conf: &mut ShellCore) {
+        let mut f = Feeder::new_from(self.text.clone());
+        while f.len() > 0 {
+            if let Some(a) = SubwordMathSubstitution::parse(&mut f, conf) {
+                self.subwords.push(Box::new(a));
+            }else if let Some(a) = SubwordCommandSubstitution::parse(&mut f, conf) {
+                self.subwords.push(Box::new(a));
+            }else if let Some(a) = SubwordBackquoted::parse(&mut f, conf, false) {
+                self.subwords.push(Box::new(a));
+            }else if let Some(a) = SubwordVariable::parse_in_double_quot(&mut f, conf) {
+                self.subwords.push(Box::new(a));
+            }else{
+                let pos = match f.scanner_here_document_string() {
+                    0 => 1, // a $ or ` which starts no expansion
+                    n => n,
+                };
+                self.subwords.push(Box::new(HereDocumentString { text: f.consume(pos) }));
+            }
+        }
+    }
+
+    /* reads the lines until the delimiter given by the word after << */
+    pub fn parse(text: &mut Feeder, conf: &mut ShellCore, word: &Word, strip_tabs: bool) -> HereDocument {
+        let (delimiter, quoted) = Self::delimiter(&word.text);
+        let mut ans = HereDocument {
+            text: String::new(),
+            pos: DebugInfo::init(text),
+            delimiter: delimiter.clone(),
+            quoted,
+            subwords: vec![],
+        };
+
+        ans.text = text.cut_here_document(&delimiter, strip_tabs, conf).concat();
+        if ! quoted {
+            ans.parse_subwords(conf);
+        }
+        ans
+    }
+}
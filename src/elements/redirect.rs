@@ -6,6 +6,7 @@ use crate::Feeder;
 use crate::ShellCore;
 use crate::operators::RedirectOp;
 use crate::elements::word::Word;
+use crate::elements::here_document::HereDocument;
 // use crate::elements::CommandElem;
 
 pub struct Redirect {
@@ -16,6 +17,7 @@ pub struct Redirect {
     pub redirect_type: RedirectOp,
     pub path: String,
     pub right_word: Option<Word>,
+    pub here_doc: Option<HereDocument>,
 }
 
 impl Redirect {
@@ -28,14 +30,19 @@ impl Redirect {
             redirect_type: RedirectOp::NoRedirect,
             path: String::new(),
             right_word: None,
+            here_doc: None,
         }
     }
 
     pub fn eval(&mut self, conf: &mut ShellCore) -> String {
+        if let Some(h) = &mut self.here_doc {
+            return h.eval(conf);
+        }
+
         if let Some(a) = &mut self.right_word {
-            let strings = a.eval(conf);
+            let strings = a.eval_without_split(conf);
             if strings.len() == 1 {
-                return a.eval(conf)[0].clone();
+                return strings[0].to_string();
             }/*else if strings.len() > 1 {
                 eprintln!("bash: {}: ambiguous redirect", &a.text);
             }*/
@@ -65,10 +72,14 @@ impl Redirect {
 
         ans.redirect_type = red.unwrap();
         ans.text += &text.consume(pos);
+        let strip_tabs = ans.redirect_type == RedirectOp::HereDoc && text.starts_with("-");
+        if strip_tabs {
+            ans.text += &text.consume(1);
+        }
         ans.text += &text.consume_blank();
 
         if ans.left_fd == -1 {
-            if ans.redirect_type == RedirectOp::Input {
+            if ans.redirect_type == RedirectOp::Input || ans.redirect_type == RedirectOp::HereDoc {
                 ans.left_fd = 0;
             }else if ans.redirect_type == RedirectOp::Output {
                 ans.left_fd = 1;
@@ -78,6 +89,9 @@ impl Redirect {
 
         if let Some(a) = Word::parse(text, conf, false) {
             ans.text += &a.text.clone();
+            if ans.redirect_type == RedirectOp::HereDoc {
+                ans.here_doc = Some(HereDocument::parse(text, conf, &a, strip_tabs));
+            }
             ans.right_word = Some(a);
         }else{
             text.rewind(backup);
@@ -32,6 +32,9 @@ impl Script {
                 conf.return_flag = false;
                 return;
             }
+            if conf.break_counter > 0 || conf.continue_counter > 0 {
+                return;
+            }
             eop = self.list_ends[i].clone();
         }
     }
@@ -46,7 +49,9 @@ impl Script {
 
     fn is_end_condition(parent: &CommandType, op: &ControlOperator) -> bool {
         ( parent == &CommandType::Paren && op == &ControlOperator::RightParen ) ||
-        ( parent == &CommandType::Case && op == &ControlOperator::DoubleSemicolon )
+        ( parent == &CommandType::Case && ( op == &ControlOperator::DoubleSemicolon
+                                            || op == &ControlOperator::SemiAnd
+                                            || op == &ControlOperator::SemiSemiAnd ) )
     }
 
     fn set_listend(text: &mut Feeder, ans: &mut Script, parent_type: &CommandType) -> bool {
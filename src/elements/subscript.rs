@@ -0,0 +1,74 @@
+//SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::calculator::calculate;
+use crate::elements::word::Word;
+
+/* [...] after the name of an array */
+pub struct Subscript {
+    pub text: String,
+    pub inner: String,
+    word: Word,
+}
+
+impl Subscript {
+    pub fn is_all(&self) -> bool {
+        self.inner == "@" || self.inner == "*"
+    }
+
+    /* expansion without arithmetic evaluation */
+    pub fn eval(&mut self, core: &mut ShellCore) -> String {
+        self.word.eval_string(core)
+    }
+
+    /* a key of an associative array */
+    pub fn eval_key(&mut self, core: &mut ShellCore) -> Result<String, String> {
+        match self.eval(core) {
+            k if k.is_empty() => Err(format!("{}: bad array subscript", self.text)),
+            k => Ok(k),
+        }
+    }
+
+    pub fn eval_index(&mut self, core: &mut ShellCore) -> Result<i64, String> {
+        if self.inner.trim().is_empty() {
+            return Err(format!("{}: bad array subscript", self.text));
+        }
+
+        calculate(self.inner.clone(), core)?.parse::<i64>()
+            .map_err(|_| format!("{}: bad array subscript", self.text))
+    }
+
+    pub fn parse(text: &mut Feeder, conf: &mut ShellCore) -> Option<Subscript> {
+        if ! text.starts_with("[") {
+            return None;
+        }
+
+        let mut depth = 0;
+        let mut end = None;
+        for (i, ch) in text.chars_after(0).enumerate() {
+            match ch {
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(i);
+                        break;
+                    }
+                },
+                '\n' => break,
+                _ => {},
+            }
+        }
+
+        let end = text.chars_after(0).take(end?).map(|c| c.len_utf8()).sum::<usize>();
+        let text_all = text.consume(end+1);
+        let inner = text_all[1..end].to_string();
+
+        Some(Subscript {
+            word: Word::parse_expansions(&inner, conf),
+            text: text_all,
+            inner,
+        })
+    }
+}
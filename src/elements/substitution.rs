@@ -1,61 +1,216 @@
 //SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+use std::env;
 use crate::ShellCore;
 use crate::debuginfo::DebugInfo;
 use crate::Feeder;
 use crate::elements::value::Value;
+use crate::elements::word::Word;
+use crate::elements::subscript::Subscript;
+use crate::utils::eval_glob;
 
 
 pub struct Substitution {
     pub text: String,
     pub name: String,
+    pub subscript: Option<Subscript>,
+    pub append: bool,
     pub value: Value,
+    pub array: Option<Vec<(Option<Subscript>, Word)>>,
     pub debug: DebugInfo,
 }
 
 impl Substitution {
-    pub fn eval(&mut self, conf: &mut ShellCore) -> Vec<String> { 
-        let mut ans = vec![];
-        ans.push(self.name.clone());
-        
-        let mut v = "".to_string();
-        for s in self.value.eval(conf){
-            v += &s;
+    pub fn set(&mut self, conf: &mut ShellCore) -> Result<(), String> {
+        if self.array.is_some() {
+            return self.set_array(conf);
         }
-        ans.push(v);
 
-        ans
+        let value = self.value.eval(conf);
+
+        if let Some(sub) = &mut self.subscript {
+            if conf.is_assoc(&self.name) {
+                let key = sub.eval_key(conf)?;
+                let value = match self.append {
+                    true  => conf.get_assoc_elem(&self.name, &key) + &value,
+                    false => value,
+                };
+                conf.set_assoc_elem(&self.name, &key, &value);
+                return Ok(());
+            }
+
+            let index = sub.eval_index(conf)?;
+            let value = match self.append {
+                true  => conf.get_array_elem(&self.name, index) + &value,
+                false => value,
+            };
+            return conf.set_array_elem(&self.name, index, &value);
+        }
+
+        let value = match self.append {
+            true  => conf.get_var(&self.name) + &value,
+            false => value,
+        };
+
+        if env::var(&self.name).is_ok() {
+            env::set_var(&self.name, value);
+        }else{
+            conf.set_var(&self.name, &value);
+        }
+        Ok(())
+    }
+
+    fn set_assoc(&mut self, conf: &mut ShellCore) -> Result<(), String> {
+        if ! self.append {
+            conf.assoc_arrays.insert(self.name.clone(), Default::default());
+        }
+
+        for (sub, word) in self.array.as_mut().unwrap() {
+            let key = match sub {
+                Some(s) => s.eval_key(conf)?,
+                None => return Err(format!("{}: {}: must use subscript when assigning associative array",
+                                           self.name, word.text)),
+            };
+            let value = word.eval_string(conf);
+            conf.set_assoc_elem(&self.name, &key, &value);
+        }
+        Ok(())
+    }
+
+    fn set_array(&mut self, conf: &mut ShellCore) -> Result<(), String> {
+        if conf.is_assoc(&self.name) {
+            return self.set_assoc(conf);
+        }
+
+        match self.append {
+            true  => conf.append_array(&self.name, vec![]),
+            false => conf.set_array(&self.name, vec![]),
+        }
+
+        /* an element without a subscript goes after the last assigned one */
+        let mut next = conf.array_index(&self.name, -1).map_or(0, |i| i + 1);
+        for (sub, word) in self.array.as_mut().unwrap() {
+            if let Some(s) = sub {
+                let index = s.eval_index(conf)?;
+                let value = word.eval_string(conf);
+                conf.set_array_elem(&self.name, index, &value)?;
+                next = conf.array_index(&self.name, index).unwrap() + 1;
+                continue;
+            }
+
+            for s in &word.eval(conf) {
+                for v in eval_glob(s) {
+                    conf.set_array_elem(&self.name, next as i64, &v)?;
+                    next += 1;
+                }
+            }
+        }
+        Ok(())
     }
 
     pub fn get_text(&self) -> String { self.text.clone() }
 
-    pub fn new(text: &Feeder, name: String, value: Value) -> Substitution{
+    pub fn new(text: &Feeder) -> Substitution{
         Substitution {
-            text: name.clone() + "=" + &value.text.clone(),
-            name: name, 
-            value: value,
+            text: String::new(),
+            name: String::new(),
+            subscript: None,
+            append: false,
+            value: Value::new(),
+            array: None,
             debug: DebugInfo::init(text)
         }
     }
 
+    /* ( word [sub]=word ... ) */
+    fn parse_array(text: &mut Feeder, conf: &mut ShellCore, ans: &mut Substitution) -> bool {
+        let mut elems = vec![];
+        ans.text += &text.consume(1);
+
+        loop {
+            ans.text += &text.consume_blank_return();
+            ans.text += &text.consume_comment();
+
+            if text.len() == 0 {
+                if ! text.feed_additional_line(conf) {
+                    return false;
+                }
+                continue;
+            }
+
+            if text.starts_with(")") {
+                ans.text += &text.consume(1);
+                break;
+            }
+
+            let backup = text.clone();
+            let mut sub = None;
+            if let Some(s) = Subscript::parse(text, conf) {
+                if text.starts_with("=") {
+                    ans.text += &s.text;
+                    ans.text += &text.consume(1);
+                    sub = Some(s);
+                }else{
+                    text.rewind(backup);
+                }
+            }
+
+            let word = match Word::parse(text, conf, false) {
+                Some(w) => w,
+                None if sub.is_some() => Word {
+                    text: String::new(),
+                    pos: DebugInfo::init(text),
+                    subwords: vec![],
+                },
+                None => return false,
+            };
+            ans.text += &word.text;
+            elems.push((sub, word));
+        }
+
+        ans.array = Some(elems);
+        true
+    }
+
     pub fn parse(text: &mut Feeder, conf: &mut ShellCore) -> Option<Substitution> {
         let backup = text.clone();
+        let mut ans = Substitution::new(text);
+
         let varname_pos = text.scanner_name(0);
-        let var_part = text.consume(varname_pos);//VarName::new(text, varname_pos);
+        if varname_pos == 0 {
+            return None;
+        }
+        ans.name = text.consume(varname_pos);
+        ans.text += &ans.name.clone();
+
+        if let Some(s) = Subscript::parse(text, conf) {
+            ans.text += &s.text;
+            ans.subscript = Some(s);
+        }
 
-        if ! text.starts_with("=") {
+        if text.starts_with("+=") {
+            ans.append = true;
+            ans.text += &text.consume(2);
+        }else if text.starts_with("=") {
+            ans.text += &text.consume(1);
+        }else{
             text.rewind(backup);
             return None;
         }
-        text.consume(1); // consume of "=" 
- 
+
+        if ans.subscript.is_none() && text.starts_with("(") {
+            if ! Substitution::parse_array(text, conf, &mut ans) {
+                text.rewind(backup);
+                return None;
+            }
+            return Some(ans);
+        }
+
         if let Some(value_part) = Value::parse(text, conf){
-            Some(Substitution::new(text, var_part, value_part))
-        }else{ // empty value
-            let empty_word = Value::new();
-            Some(Substitution::new(text, var_part, empty_word))
+            ans.text += &value_part.text;
+            ans.value = value_part;
         }
+        Some(ans)
     }
 }
-
@@ -1,10 +1,14 @@
 //SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+pub mod ansi_c_quoted;
+pub mod backquoted;
 pub mod braced;
 pub mod command_substitution;
 pub mod double_quoted;
+pub mod locale_quoted;
 pub mod math_substitution;
+pub mod process_substitution;
 pub mod string_double_quoted;
 pub mod string_non_quoted;
 pub mod single_quoted;
@@ -12,25 +16,37 @@ pub mod tilde;
 pub mod variable;
 
 use crate::{Feeder, ShellCore}; 
+use crate::elements::word::QuotedString;
 
+use self::ansi_c_quoted::SubwordAnsiCQuoted;
+use self::locale_quoted::SubwordLocaleQuoted;
+use self::backquoted::SubwordBackquoted;
 use self::command_substitution::SubwordCommandSubstitution;
 use self::math_substitution::SubwordMathSubstitution;
+use self::process_substitution::SubwordProcessSubstitution;
 use self::string_non_quoted::SubwordStringNonQuoted;
 use self::double_quoted::SubwordDoubleQuoted;
 use self::single_quoted::SubwordSingleQuoted;
 use self::braced::SubwordBraced;
 use self::variable::SubwordVariable;
 
+/* eval gives the fields for each alternative of brace expansion;
+   the results of expansions (split_by_ifs) are unquoted, and split is false in quotes */
 pub trait Subword {
-    fn eval(&mut self, _conf: &mut ShellCore, remove_lf: bool) -> Vec<Vec<String>>;
+    fn eval(&mut self, _conf: &mut ShellCore, split: bool) -> Vec<Vec<QuotedString>>;
     fn get_text(&self) -> String;
     fn permit_lf(&self) -> bool {false}
+    fn split_by_ifs(&self) -> bool {false} // subject to field splitting when unquoted
 }
 
 pub fn parse_in_arg(text: &mut Feeder, conf: &mut ShellCore, is_in_brace: bool) -> Option<Box<dyn Subword>> {
-    if let Some(a) = SubwordMathSubstitution::parse(text, conf)                   {Some(Box::new(a))}
+    if let Some(a) = SubwordProcessSubstitution::parse(text, conf)                {Some(Box::new(a))}
+    else if let Some(a) = SubwordAnsiCQuoted::parse(text, conf)                   {Some(Box::new(a))}
+    else if let Some(a) = SubwordLocaleQuoted::parse(text, conf)                  {Some(Box::new(a))}
+    else if let Some(a) = SubwordMathSubstitution::parse(text, conf)              {Some(Box::new(a))}
     else if let Some(a) = SubwordCommandSubstitution::parse(text, conf)           {Some(Box::new(a))}
-    else if let Some(a) = SubwordVariable::parse(text)                            {Some(Box::new(a))}
+    else if let Some(a) = SubwordBackquoted::parse(text, conf, false)             {Some(Box::new(a))}
+    else if let Some(a) = SubwordVariable::parse(text, conf)                      {Some(Box::new(a))}
     else if let Some(a) = SubwordBraced::parse(text, conf)                        {Some(Box::new(a))}
     else if let Some(a) = SubwordSingleQuoted::parse(text, conf)                  {Some(Box::new(a))}
     else if let Some(a) = SubwordDoubleQuoted::parse(text, conf)                  {Some(Box::new(a))}
@@ -39,9 +55,12 @@ pub fn parse_in_arg(text: &mut Feeder, conf: &mut ShellCore, is_in_brace: bool)
 }
 
 pub fn parse_in_value(text: &mut Feeder, conf: &mut ShellCore) -> Option<Box<dyn Subword>> {
-    if let Some(a) = SubwordMathSubstitution::parse(text, conf)               {Some(Box::new(a))}
+    if let Some(a) = SubwordAnsiCQuoted::parse(text, conf)                    {Some(Box::new(a))}
+    else if let Some(a) = SubwordLocaleQuoted::parse(text, conf)              {Some(Box::new(a))}
+    else if let Some(a) = SubwordMathSubstitution::parse(text, conf)          {Some(Box::new(a))}
     else if let Some(a) = SubwordCommandSubstitution::parse(text, conf)       {Some(Box::new(a))}
-    else if let Some(a) = SubwordVariable::parse(text)                        {Some(Box::new(a))}
+    else if let Some(a) = SubwordBackquoted::parse(text, conf, false)         {Some(Box::new(a))}
+    else if let Some(a) = SubwordVariable::parse(text, conf)                  {Some(Box::new(a))}
     else if let Some(a) = SubwordSingleQuoted::parse(text, conf)              {Some(Box::new(a))}
     else if let Some(a) = SubwordDoubleQuoted::parse(text, conf)              {Some(Box::new(a))}
     else if let Some(a) = SubwordStringNonQuoted::parse(text, false, true)    {Some(Box::new(a))}
@@ -0,0 +1,47 @@
+//SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::debuginfo::DebugInfo;
+use crate::ShellCore;
+use crate::Feeder;
+use crate::utils::unescape_ansi_c;
+
+use crate::elements::subword::Subword;
+use crate::elements::word::QuotedString;
+
+pub struct SubwordAnsiCQuoted {
+    pub text: String,
+    pub pos: DebugInfo,
+}
+
+impl Subword for SubwordAnsiCQuoted {
+    fn eval(&mut self, _conf: &mut ShellCore, _: bool) -> Vec<Vec<QuotedString>> {
+        let strip = &self.text[2..self.text.len()-1];
+        vec!(vec!(QuotedString::quoted(&unescape_ansi_c(strip))))
+    }
+
+    fn get_text(&self) -> String {
+        self.text.clone()
+    }
+
+    fn permit_lf(&self) -> bool {true}
+}
+
+impl SubwordAnsiCQuoted {
+/* parser for a string such as $'a\tb' */
+    pub fn parse(text: &mut Feeder, core: &mut ShellCore) -> Option<SubwordAnsiCQuoted> {
+        if ! text.starts_with("$'") {
+            return None;
+        };
+
+        let mut pos = text.scanner_ansi_c_quoted();
+        while pos == text.len() {
+            if !text.feed_additional_line(core){
+                return None;
+            }
+            pos = text.scanner_ansi_c_quoted();
+        }
+        Some(SubwordAnsiCQuoted{text: text.consume(pos+1),
+                                pos: DebugInfo::init(text)})
+    }
+}
@@ -0,0 +1,86 @@
+//SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::debuginfo::DebugInfo;
+use crate::ShellCore;
+use crate::Feeder;
+
+use crate::elements::subword::Subword;
+use crate::elements::word::QuotedString;
+use crate::elements::command::Command;
+use crate::elements::command::paren::CommandParen;
+
+pub struct SubwordBackquoted {
+    pub text: String,
+    pub pos: DebugInfo,
+    pub com: Option<CommandParen>, // None if no command is given
+}
+
+impl Subword for SubwordBackquoted {
+    fn eval(&mut self, conf: &mut ShellCore, _: bool) -> Vec<Vec<QuotedString>> {
+        match self.com.as_mut() {
+            Some(com) => {
+                com.substitution = true;
+                com.exec(conf);
+                vec!(vec!(QuotedString::unquoted(&com.substitution_text)))
+            },
+            None => vec!(vec!(QuotedString::default())),
+        }
+    }
+
+    fn split_by_ifs(&self) -> bool {true}
+
+    fn get_text(&self) -> String {
+        self.text.clone()
+    }
+}
+
+/* a backslash remains except before $ ` \ (and " in double quotes) */
+fn remove_backslashes(s: &str, in_double_quot: bool) -> String {
+    let specials = if in_double_quot { "$`\\\"" } else { "$`\\" };
+
+    let mut ans = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('\\', Some(&c)) if specials.contains(c) => {
+                ans.push(c);
+                chars.next();
+            },
+            _ => ans.push(ch),
+        }
+    }
+    ans
+}
+
+impl SubwordBackquoted {
+    pub fn parse(text: &mut Feeder, conf: &mut ShellCore, in_double_quot: bool) -> Option<SubwordBackquoted> {
+        if ! text.starts_with("`") {
+            return None;
+        }
+
+        let mut pos = text.scanner_backquoted();
+        while pos == text.len() {
+            if ! text.feed_additional_line(conf) {
+                return None;
+            }
+            pos = text.scanner_backquoted();
+        }
+
+        let s = text.consume(pos+1);
+        let inner = remove_backslashes(&s[1..s.len()-1], in_double_quot);
+        let com = match inner.trim().is_empty() {
+            true  => None,
+            false => {
+                let mut f = Feeder::new_from(format!("({}\n)", inner));
+                CommandParen::parse(&mut f, conf, true)
+            },
+        };
+
+        Some(SubwordBackquoted {
+            text: s,
+            pos: DebugInfo::init(text),
+            com,
+        })
+    }
+}
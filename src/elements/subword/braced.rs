@@ -5,7 +5,7 @@ use crate::debuginfo::DebugInfo;
 // use crate::elements::CommandElem;
 use crate::ShellCore;
 use crate::Feeder;
-use crate::elements::word::Word;
+use crate::elements::word::{Word, QuotedString};
 
 use crate::elements::word::word_in_brace;
 use crate::elements::subword::Subword;
@@ -16,12 +16,17 @@ pub struct SubwordBraced {
     pub pos: DebugInfo,
     pub words: Vec<Word>,
     pub complete: bool,
+    pub sequence: Option<Vec<String>>, // elements of {x..y[..incr]}
 }
 
 impl Subword for SubwordBraced {
-    fn eval(&mut self, conf: &mut ShellCore, _: bool) -> Vec<Vec<String>> {
+    fn eval(&mut self, conf: &mut ShellCore, split: bool) -> Vec<Vec<QuotedString>> {
+        if let Some(seq) = &self.sequence {
+            return seq.iter().map(|s| vec!(QuotedString::quoted(s))).collect();
+        }
+
         if self.complete {
-            self.eval_complete(conf)
+            self.eval_complete(conf, split)
         }else{
             self.eval_incomplete(conf)
         }
@@ -41,47 +46,49 @@ impl SubwordBraced {
             pos: DebugInfo::init(text),
             words: vec![],
             complete: false,
+            sequence: None,
         }
     }
 
-    fn eval_complete(&mut self, conf: &mut ShellCore) -> Vec<Vec<String>> {
+    fn eval_complete(&mut self, conf: &mut ShellCore, split: bool) -> Vec<Vec<QuotedString>> {
         let mut ans = vec![];
         for word in &mut self.words {
-            ans.push(word.eval(conf));
+            ans.append(&mut word.eval_alternatives(conf, split));
         };
         ans
     }
 
-    fn eval_incomplete(&mut self, conf: &mut ShellCore) -> Vec<Vec<String>> {
+    fn eval_incomplete(&mut self, conf: &mut ShellCore) -> Vec<Vec<QuotedString>> {
         if self.words.len() == 0 {
-            return vec!(vec!(self.text.clone()));
+            return vec!(vec!(QuotedString::from_escaped(&self.text)));
         }else if self.words.len() == 1 {
             let mut ans = vec![];
-            let mut v = "{".to_string();
-            v += &self.words[0].eval(conf).join(" ");
+            let mut v = QuotedString::unquoted("{");
+            v += &QuotedString::join(&self.words[0].eval(conf), " ");
             if let Some(c) = self.text.chars().last() {
                 if c == ',' || c == '}' {
-                    ans.push(v + &c.to_string());
-                }else{
-                    ans.push(v);
+                    v += &QuotedString::unquoted(&c.to_string());
                 }
             }
+            ans.push(v);
             return vec!(ans);
         }
 
         let mut ans = vec![];
         for word in &mut self.words {
             let vs = word.eval(conf);
-            ans = combine_with(&ans, &vs, ",");
+            ans = combine_with(&ans, &vs, &QuotedString::unquoted(","));
         };
 
         for v in &mut ans {
-            *v = "{".to_owned() + v;
+            let mut s = QuotedString::unquoted("{");
+            s += v;
             if let Some(c) = self.text.chars().last() {
                 if c == ',' || c == '}' {
-                    *v += &c.to_string();
+                    s += &QuotedString::unquoted(&c.to_string());
                 }
             };
+            *v = s;
         }
 
         vec!(ans)
@@ -117,6 +124,10 @@ impl SubwordBraced {
             };
         };
 
+        if ans.complete && ans.words.len() == 1 {
+            ans.sequence = sequence(&ans.words[0].text);
+        }
+
         if ans.words.len() < 2 {
             ans.complete = false;
             return Some(ans);
@@ -125,3 +136,76 @@ impl SubwordBraced {
         Some(ans)
     }
 }
+
+fn is_integer(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    ! digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/* zero padding is applied when either end has a leading zero */
+fn padding_width(start: &str, end: &str) -> usize {
+    let zero_padded = |s: &str| {
+        let digits = s.strip_prefix('-').unwrap_or(s);
+        digits.len() > 1 && digits.starts_with('0')
+    };
+
+    match zero_padded(start) || zero_padded(end) {
+        true  => std::cmp::max(start.len(), end.len()),
+        false => 0,
+    }
+}
+
+fn integer_sequence(start: i64, end: i64, step: i64, width: usize) -> Vec<String> {
+    let format = |n: i64| match n < 0 {
+        true  => format!("-{:0w$}", n.unsigned_abs(), w = width.saturating_sub(1)),
+        false => format!("{:0w$}", n, w = width),
+    };
+
+    let step = if start <= end { step } else { -step };
+    let mut ans = vec![];
+    let mut n = start;
+    while (start <= end && n <= end) || (start > end && n >= end) {
+        ans.push(format(n));
+        n = match n.checked_add(step) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    ans
+}
+
+fn char_sequence(start: char, end: char, step: i64) -> Vec<String> {
+    let (start, end) = (start as i64, end as i64);
+    integer_sequence(start, end, step, 0).iter()
+        .filter_map(|n| n.parse::<u32>().ok().and_then(char::from_u32))
+        .map(|c| c.to_string())
+        .collect()
+}
+
+/* the elements of a sequence expression x..y[..incr]; None if it is invalid */
+fn sequence(text: &str) -> Option<Vec<String>> {
+    let parts = text.split("..").collect::<Vec<&str>>();
+    if parts.len() != 2 && parts.len() != 3 {
+        return None;
+    }
+
+    let step = match parts.get(2) {
+        Some(s) if is_integer(s) => s.parse::<i64>().ok()?.checked_abs()?.max(1),
+        Some(_) => return None,
+        None => 1,
+    };
+
+    let (start, end) = (parts[0], parts[1]);
+    if is_integer(start) && is_integer(end) {
+        let width = padding_width(start, end);
+        return Some(integer_sequence(start.parse().ok()?, end.parse().ok()?, step, width));
+    }
+
+    let mut start_chars = start.chars();
+    let mut end_chars = end.chars();
+    match (start_chars.next(), start_chars.next(), end_chars.next(), end_chars.next()) {
+        (Some(s), None, Some(e), None) if s.is_ascii_alphabetic() && e.is_ascii_alphabetic()
+            => Some(char_sequence(s, e, step)),
+        _ => None,
+    }
+}
@@ -6,6 +6,7 @@ use crate::ShellCore;
 use crate::Feeder;
 
 use crate::elements::subword::Subword;
+use crate::elements::word::QuotedString;
 use crate::elements::command::Command;
 use crate::elements::command::paren::CommandParen;
 
@@ -17,21 +18,14 @@ pub struct SubwordCommandSubstitution {
 }
 
 impl Subword for SubwordCommandSubstitution {
-    fn eval(&mut self, conf: &mut ShellCore, remove_lf: bool) -> Vec<Vec<String>> {
+    fn eval(&mut self, conf: &mut ShellCore, _: bool) -> Vec<Vec<QuotedString>> {
         self.com.substitution = true;
         self.com.exec(conf);
-
-        if ! remove_lf {
-            return vec!(vec!(self.com.substitution_text.clone()));
-        }
-        let ans = self.com.substitution_text
-                .split(" ")
-                .map(|x| x.to_string())
-                .collect::<Vec<String>>();
-
-        vec!(ans)
+        vec!(vec!(QuotedString::unquoted(&self.com.substitution_text)))
     }
 
+    fn split_by_ifs(&self) -> bool {true}
+
     fn get_text(&self) -> String {
         self.text.clone()
     }
@@ -9,6 +9,9 @@ use crate::elements::subword::Subword;
 use crate::elements::subword::string_double_quoted::SubwordStringDoubleQuoted;
 use crate::elements::subword::variable::SubwordVariable;
 use crate::elements::subword::command_substitution::SubwordCommandSubstitution;
+use crate::elements::subword::backquoted::SubwordBackquoted;
+use crate::elements::subword::math_substitution::SubwordMathSubstitution;
+use crate::elements::word::QuotedString;
 use crate::utils::combine;
 
 pub struct SubwordDoubleQuoted {
@@ -18,7 +21,12 @@ pub struct SubwordDoubleQuoted {
 }
 
 impl Subword for SubwordDoubleQuoted {
-    fn eval(&mut self, conf: &mut ShellCore, _: bool) -> Vec<Vec<String>> {
+    fn eval(&mut self, conf: &mut ShellCore, _: bool) -> Vec<Vec<QuotedString>> {
+        if self.subwords.is_empty() { // "" is an empty word
+            return vec!(vec!(QuotedString::default()));
+        }
+
+        let in_double_quot = conf.in_double_quot;
         conf.in_double_quot = true;
 
         let mut vvv = vec![];
@@ -31,17 +39,11 @@ impl Subword for SubwordDoubleQuoted {
             strings = combine(&mut strings, ss);
         }
 
-        let mut ans = vec![];
-        for ss in strings {
-            let mut anselem = vec![];
-            for s in ss {
-                let x = s.replace("*", "\\*");
-                anselem.push(x);
-            }
-            ans.push(anselem);
-        }
+        let ans = strings.iter()
+            .map(|ss| ss.iter().map(|s| s.to_quoted()).collect())
+            .collect();
 
-        conf.in_double_quot = false;
+        conf.in_double_quot = in_double_quot;
         ans
     }
 
@@ -66,18 +68,28 @@ impl SubwordDoubleQuoted {
             subwords: vec![],
         };
     
+        let backup = text.clone();
         ans.text += &text.consume(1);
     
         loop {
-            if let Some(a) = SubwordCommandSubstitution::parse(text, conf) {
+            if let Some(a) = SubwordMathSubstitution::parse(text, conf) {
+                ans.text += &a.text.clone();
+                ans.subwords.push(Box::new(a));
+            }else if let Some(a) = SubwordCommandSubstitution::parse(text, conf) {
+                ans.text += &a.text.clone();
+                ans.subwords.push(Box::new(a));
+            }else if let Some(a) = SubwordBackquoted::parse(text, conf, true) {
                 ans.text += &a.text.clone();
                 ans.subwords.push(Box::new(a));
-            }else if let Some(a) = SubwordVariable::parse(text) {
+            }else if let Some(a) = SubwordVariable::parse_in_double_quot(text, conf) {
                 ans.text += &a.text.clone();
                 ans.subwords.push(Box::new(a));
             }else if let Some(a) = SubwordStringDoubleQuoted::parse(text, conf) {
                 ans.text += &a.text.clone();
                 ans.subwords.push(Box::new(a));
+            }else if ! text.starts_with("\"") { // unterminated
+                text.rewind(backup);
+                return None;
             }
 
             if text.starts_with("\"") {
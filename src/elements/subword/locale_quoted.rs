@@ -0,0 +1,126 @@
+//SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use std::fs;
+use std::convert::TryInto;
+use crate::debuginfo::DebugInfo;
+use crate::ShellCore;
+use crate::Feeder;
+
+use crate::elements::subword::Subword;
+use crate::elements::word::QuotedString;
+use crate::elements::subword::double_quoted::SubwordDoubleQuoted;
+
+pub struct SubwordLocaleQuoted {
+    pub text: String,
+    pub pos: DebugInfo,
+    pub quoted: SubwordDoubleQuoted,
+}
+
+impl Subword for SubwordLocaleQuoted {
+    fn eval(&mut self, conf: &mut ShellCore, split: bool) -> Vec<Vec<QuotedString>> {
+        let msgid = &self.quoted.text[1..self.quoted.text.len()-1];
+
+        /* the translation is expanded as the contents of double quotes */
+        if let Some(msgstr) = translate(msgid, conf) {
+            let mut f = Feeder::new_from(format!("\"{}\"", msgstr));
+            if let Some(mut q) = SubwordDoubleQuoted::parse(&mut f, conf) {
+                return q.eval(conf, split);
+            }
+        }
+        self.quoted.eval(conf, split)
+    }
+
+    fn get_text(&self) -> String {
+        self.text.clone()
+    }
+
+    fn permit_lf(&self) -> bool {true}
+}
+
+impl SubwordLocaleQuoted {
+/* parser for a string such as $"hello" */
+    pub fn parse(text: &mut Feeder, conf: &mut ShellCore) -> Option<SubwordLocaleQuoted> {
+        if ! text.starts_with("$\"") {
+            return None;
+        };
+
+        let backup = text.clone();
+        text.consume(1);
+
+        match SubwordDoubleQuoted::parse(text, conf) {
+            Some(q) => Some(SubwordLocaleQuoted{
+                text: "$".to_owned() + &q.text,
+                pos: DebugInfo::init(text),
+                quoted: q}),
+            None => {
+                text.rewind(backup);
+                None
+            },
+        }
+    }
+}
+
+/* ja_JP.UTF-8 -> ja_JP.UTF-8, ja_JP, ja */
+fn locale_names(locale: &str) -> Vec<String> {
+    let mut ans = vec!(locale.to_string());
+    for sep in ['.', '_'] {
+        if let Some((head, _)) = locale.split_once(sep) {
+            ans.push(head.to_string());
+        }
+    }
+    ans
+}
+
+/* a message in $TEXTDOMAINDIR/<locale>/LC_MESSAGES/$TEXTDOMAIN.mo */
+fn translate(msgid: &str, conf: &mut ShellCore) -> Option<String> {
+    let domain = conf.find_var("TEXTDOMAIN").filter(|d| ! d.is_empty())?;
+    let dir = conf.find_var("TEXTDOMAINDIR")
+                  .filter(|d| ! d.is_empty())
+                  .unwrap_or("/usr/share/locale".to_string());
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+                  .filter_map(|v| conf.find_var(v))
+                  .find(|l| ! l.is_empty())?;
+
+    if locale == "C" || locale == "POSIX" {
+        return None;
+    }
+
+    locale_names(&locale).iter()
+        .map(|name| format!("{}/{}/LC_MESSAGES/{}.mo", dir, name, domain))
+        .filter_map(|path| fs::read(path).ok())
+        .find_map(|data| lookup_catalog(&data, msgid))
+}
+
+/* search of a GNU gettext message catalog */
+fn lookup_catalog(data: &[u8], msgid: &str) -> Option<String> {
+    let word = |pos: usize, big_endian: bool| {
+        let bytes: [u8; 4] = data.get(pos..pos+4)?.try_into().ok()?;
+        match big_endian {
+            true  => Some(u32::from_be_bytes(bytes) as usize),
+            false => Some(u32::from_le_bytes(bytes) as usize),
+        }
+    };
+
+    let big_endian = match word(0, false)? {
+        0x950412de => false,
+        0xde120495 => true,
+        _ => return None,
+    };
+
+    let string = |table: usize, i: usize| {
+        let len = word(table + 8*i, big_endian)?;
+        let offset = word(table + 8*i + 4, big_endian)?;
+        data.get(offset..offset+len)
+    };
+
+    let num = word(8, big_endian)?;
+    let originals = word(12, big_endian)?;
+    let translations = word(16, big_endian)?;
+    for i in 0..num {
+        if string(originals, i)? == msgid.as_bytes() {
+            return String::from_utf8(string(translations, i)?.to_vec()).ok();
+        }
+    }
+    None
+}
@@ -6,6 +6,7 @@ use crate::ShellCore;
 use crate::Feeder;
 
 use crate::elements::subword::Subword;
+use crate::elements::word::QuotedString;
 use crate::elements::command::Command;
 use crate::elements::command::double_paren::CommandDoubleParen;
 
@@ -17,12 +18,12 @@ pub struct SubwordMathSubstitution {
 }
 
 impl Subword for SubwordMathSubstitution {
-    fn eval(&mut self, conf: &mut ShellCore, _: bool) -> Vec<Vec<String>> {
+    fn eval(&mut self, conf: &mut ShellCore, _: bool) -> Vec<Vec<QuotedString>> {
         self.com.substitution = true;
         self.com.exec(conf);
 
 //        if self.is_value {
-            return vec!(vec!(self.com.substitution_text.clone()));
+            return vec!(vec!(QuotedString::unquoted(&self.com.substitution_text)));
  //       }
 
             /*
@@ -35,6 +36,8 @@ impl Subword for SubwordMathSubstitution {
         */
     }
 
+    fn split_by_ifs(&self) -> bool {true}
+
     fn get_text(&self) -> String {
         self.text.clone()
     }
@@ -0,0 +1,82 @@
+//SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use nix::unistd::{close, fork, pipe, ForkResult};
+use std::process::exit;
+
+use crate::debuginfo::DebugInfo;
+use crate::ShellCore;
+use crate::Feeder;
+use crate::file_descs::FileDescs;
+
+use crate::elements::subword::Subword;
+use crate::elements::word::QuotedString;
+use crate::elements::command::Command;
+use crate::elements::command::paren::CommandParen;
+
+pub struct SubwordProcessSubstitution {
+    pub text: String,
+    pub pos: DebugInfo,
+    pub com: CommandParen,
+    pub input: bool, // true for <(...), false for >(...)
+}
+
+impl Subword for SubwordProcessSubstitution {
+    fn eval(&mut self, conf: &mut ShellCore, _: bool) -> Vec<Vec<QuotedString>> {
+        let p = pipe().expect("Pipe cannot open");
+        let (shell_end, child_end, child_fd) = match self.input {
+            true  => (p.0, p.1, 1),
+            false => (p.1, p.0, 0),
+        };
+
+        match unsafe{fork()} {
+            Ok(ForkResult::Child) => {
+                for (fd, _) in &conf.proc_subs {
+                    let _ = close(*fd);
+                }
+                close(shell_end).expect("Can't close a pipe end");
+                FileDescs::dup_and_close(child_end, child_fd);
+                if let Some(s) = &mut self.com.script {
+                    s.exec(conf);
+                }
+                exit(conf.get_var("?").parse::<i32>().unwrap_or(1));
+            },
+            Ok(ForkResult::Parent { child } ) => {
+                close(child_end).expect("Can't close a pipe end");
+                conf.proc_subs.push((shell_end, child));
+            },
+            Err(err) => panic!("Failed to fork. {}", err),
+        }
+
+        vec!(vec!(QuotedString::quoted(&format!("/dev/fd/{}", shell_end))))
+    }
+
+    fn get_text(&self) -> String {
+        self.text.clone()
+    }
+}
+
+impl SubwordProcessSubstitution {
+    pub fn parse(text: &mut Feeder, conf: &mut ShellCore) -> Option<SubwordProcessSubstitution> {
+        if ! text.starts_with("<(") && ! text.starts_with(">(") {
+            return None;
+        }
+
+        let backup = text.clone();
+        let direction = text.consume(1);
+
+        if let Some(e) = CommandParen::parse(text, conf, true){
+            let ans = SubwordProcessSubstitution {
+                text: direction.clone() + &e.get_text(),
+                pos: DebugInfo::init(text),
+                com: e,
+                input: direction == "<",
+            };
+    
+            Some(ans)
+        }else{
+            text.rewind(backup);
+            None
+        }
+    }
+}
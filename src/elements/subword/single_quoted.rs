@@ -7,6 +7,7 @@ use crate::Feeder;
 //use crate::feeder::scanner::*;
 
 use crate::elements::subword::Subword;
+use crate::elements::word::QuotedString;
 
 
 pub struct SubwordSingleQuoted {
@@ -16,10 +17,9 @@ pub struct SubwordSingleQuoted {
 }
 
 impl Subword for SubwordSingleQuoted {
-    fn eval(&mut self, _conf: &mut ShellCore, _: bool) -> Vec<Vec<String>> {
+    fn eval(&mut self, _conf: &mut ShellCore, _: bool) -> Vec<Vec<QuotedString>> {
         let strip = self.text[1..self.text.len()-1].to_string();
-        let s = strip.replace("\\", "\\\\").replace("*", "\\*"); 
-        vec!(vec!(s))
+        vec!(vec!(QuotedString::quoted(&strip)))
     }
 
     fn get_text(&self) -> String {
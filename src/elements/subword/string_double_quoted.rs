@@ -7,6 +7,7 @@ use crate::Feeder;
 //use crate::feeder::scanner::*;
 
 use crate::elements::subword::Subword;
+use crate::elements::word::QuotedString;
 
 pub struct SubwordStringDoubleQuoted {
     pub text: String,
@@ -19,12 +20,26 @@ impl Subword for SubwordStringDoubleQuoted {
         self.text.clone()
     }
 
-    fn eval(&mut self, _conf: &mut ShellCore, remove_lf: bool) -> Vec<Vec<String>> {
-        if remove_lf {
-            vec!(vec!(self.text.replace("\n", " ")))
-        }else{
-            vec!(vec!(self.text.clone()))
+    /* gives the raw string; a backslash in double quotes only escapes $ ` " \ and a newline */
+    fn eval(&mut self, _conf: &mut ShellCore, _: bool) -> Vec<Vec<QuotedString>> {
+        let mut ans = String::new();
+        let mut chars = self.text.chars().peekable();
+        while let Some(ch) = chars.next() {
+            if ch != '\\' {
+                ans.push(ch);
+                continue;
+            }
+
+            match chars.peek() {
+                Some('\n') => {chars.next();},
+                Some(&c) if "$`\"\\".contains(c) => {
+                    ans.push(c);
+                    chars.next();
+                },
+                _ => ans.push(ch),
+            }
         }
+        vec!(vec!(QuotedString::quoted(&ans)))
     }
 }
 
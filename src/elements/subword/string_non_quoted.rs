@@ -7,6 +7,7 @@ use crate::Feeder;
 //use crate::feeder::scanner::*;
 
 use crate::elements::subword::Subword;
+use crate::elements::word::QuotedString;
 
 pub struct SubwordStringNonQuoted {
     pub text: String,
@@ -19,13 +20,13 @@ impl Subword for SubwordStringNonQuoted {
         self.text.clone()
     }
 
-    fn eval(&mut self, _conf: &mut ShellCore, _: bool) -> Vec<Vec<String>> {
+    fn eval(&mut self, _conf: &mut ShellCore, _: bool) -> Vec<Vec<QuotedString>> {
         /*
         if self.is_value {
             vec!(vec!(self.text.clone()))
         }else{
         */
-            vec!(vec!(self.text.replace("\n", " ")))
+            vec!(vec!(QuotedString::from_escaped(&self.text.replace("\n", " "))))
         //}
     }
 }
@@ -8,6 +8,7 @@ use crate::Feeder;
 use crate::utils::expand_tilde;
 
 use crate::elements::subword::Subword;
+use crate::elements::word::QuotedString;
 
 pub struct SubwordTildePrefix {
     pub text: String,
@@ -19,8 +20,8 @@ impl Subword for SubwordTildePrefix {
         self.text.clone()
     }
 
-    fn eval(&mut self, _conf: &mut ShellCore, _: bool) -> Vec<Vec<String>> {
-        vec!(vec!(expand_tilde(&self.text).0))
+    fn eval(&mut self, _conf: &mut ShellCore, _: bool) -> Vec<Vec<QuotedString>> {
+        vec!(vec!(QuotedString::quoted(&expand_tilde(&self.text).0)))
     }
 }
 
@@ -1,34 +1,75 @@
 //SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+use std::process;
 use crate::debuginfo::DebugInfo;
 use crate::ShellCore;
 use crate::Feeder;
 //use crate::feeder::scanner::*;
 
+use crate::bash_glob::{glob_match, GLOB_SPECIALS};
+use crate::calculator::calculate;
+use crate::utils::{unescape_ansi_c, expand_prompt};
 use crate::elements::subword::Subword;
+use crate::elements::subscript::Subscript;
+use crate::elements::word::{Word, QuotedString};
 
 pub struct SubwordVariable {
     pub text: String,
     pub name: String,
+    pub prefix: String,
+    pub subscript: Option<Subscript>,
     pub empty_option: String,
-    pub empty_option_string: String,
+    pub empty_option_word: Option<Word>,
+    pub operator: String,
+    pub pattern: Option<Word>,
+    pub replacement: Option<Word>,
+    pub offset: Option<String>,
+    pub length: Option<String>,
+    pub list_suffix: String, // * or @ of ${!prefix*} and ${!prefix@}
+    pub split: bool,
+    pub word_fields: bool, // true if the result is the fields of empty_option_word
     pub pos: DebugInfo,
 }
 
 impl Subword for SubwordVariable {
-    fn eval(&mut self, conf: &mut ShellCore, _: bool) -> Vec<Vec<String>> {
-        let val = conf.get_var(&self.name);
+    fn eval(&mut self, conf: &mut ShellCore, split: bool) -> Vec<Vec<QuotedString>> {
+        self.split = split;
+        self.word_fields = false;
 
-        if val.len() == 0 {
-            vec!(vec!(self.empty_treat(conf)))
-        }else if self.empty_option == ":+" {
-            vec!(vec!(self.empty_option_string.clone()))
-        }else{
-            vec!(vec!(val))
+        if self.text == "$" { // not followed by a name
+            return single(&self.text);
+        }
+
+        if ! self.list_suffix.is_empty() {
+            let names = var_names(&self.name, conf);
+            return self.eval_list(names, self.list_suffix == "*", conf);
+        }
+
+        let is_all = self.subscript.as_ref().is_some_and(|s| s.is_all());
+        if self.prefix == "!" && ! is_all {
+            return self.eval_indirect(conf);
+        }
+
+        if self.subscript.is_some() {
+            return self.eval_array(conf);
+        }
+
+        if self.name == "@" || self.name == "*" {
+            let params = match self.offset.is_some() {
+                true  => conf.args.clone(), // ${@:0} begins with $0
+                false => conf.args[1..].to_vec(),
+            };
+            return self.eval_list(params, self.name == "*", conf);
         }
+
+        let val = conf.find_var(&self.name);
+        self.eval_value(val, conf)
     }
 
+    fn split_by_ifs(&self) -> bool {! self.word_fields}
+    fn permit_lf(&self) -> bool {self.word_fields}
+
     fn get_text(&self) -> String {
         self.text.clone()
     }
@@ -38,35 +79,341 @@ impl SubwordVariable {
     pub fn new(text: &mut Feeder) -> SubwordVariable {
         SubwordVariable {
             name: String::new(),
+            prefix: String::new(),
+            subscript: None,
             text: String::new(),
             empty_option: String::new(),
-            empty_option_string: String::new(),
+            empty_option_word: None,
+            operator: String::new(),
+            pattern: None,
+            replacement: None,
+            offset: None,
+            length: None,
+            list_suffix: String::new(),
+            split: false,
+            word_fields: false,
             pos: DebugInfo::init(text),
         }
     }
 
-    fn empty_treat(&self, conf: &mut ShellCore) -> String {
-        let opt: &str = &self.empty_option.clone();
+    fn eval_array(&mut self, conf: &mut ShellCore) -> Vec<Vec<QuotedString>> {
+        let sub = self.subscript.as_mut().unwrap();
+
+        if sub.is_all() {
+            if self.operator == "@A" {
+                return single(&declare_array_string(&self.name, conf));
+            }
+
+            let values = match self.prefix.as_str() {
+                "!" => conf.get_array_indices(&self.name),
+                _   => conf.get_array_values(&self.name),
+            };
+            let star = sub.inner == "*";
+            return self.eval_list(values, star, conf);
+        }
+
+        let value = match conf.is_assoc(&self.name) {
+            true  => sub.eval_key(conf).map(|k| conf.find_assoc_elem(&self.name, &k)),
+            false => sub.eval_index(conf).map(|i| conf.find_array_elem(&self.name, i)),
+        };
+
+        match value {
+            Ok(v) => self.eval_value(v, conf),
+            Err(msg) => {
+                eprintln!("bash: {}", msg);
+                conf.set_var("?", "1");
+                single("")
+            },
+        }
+    }
+
+    /* ${!name}: the value of name is the name of the variable */
+    fn eval_indirect(&mut self, conf: &mut ShellCore) -> Vec<Vec<QuotedString>> {
+        let reference = match self.subscript.as_mut() {
+            None => conf.find_var(&self.name),
+            Some(sub) => match conf.is_assoc(&self.name) {
+                true  => sub.eval_key(conf).ok().and_then(|k| conf.find_assoc_elem(&self.name, &k)),
+                false => sub.eval_index(conf).ok().and_then(|i| conf.find_array_elem(&self.name, i)),
+            },
+        };
+
+        let target = match reference {
+            Some(r) if ! r.is_empty() => r,
+            _ => {
+                let sub = self.subscript.as_ref().map_or(String::new(), |s| s.text.clone());
+                return self.indirect_error(&format!("{}{}: invalid indirect expansion", self.name, sub), conf);
+            },
+        };
+
+        let mut f = Feeder::new_from(target.clone());
+        let pos = f.scanner_name_or_parameter();
+        let name = f.consume(pos);
+        let sub = Subscript::parse(&mut f, conf);
+        if name.is_empty() || f.len() > 0 {
+            return self.indirect_error(&format!("{}: invalid variable name", target), conf);
+        }
+
+        let value = match sub {
+            None => conf.find_var(&name),
+            Some(s) if s.is_all() => Some(conf.get_array_values(&name).join(" ")),
+            Some(mut s) => match conf.is_assoc(&name) {
+                true  => s.eval_key(conf).ok().and_then(|k| conf.find_assoc_elem(&name, &k)),
+                false => s.eval_index(conf).ok().and_then(|i| conf.find_array_elem(&name, i)),
+            },
+        };
+        self.eval_value(value, conf)
+    }
+
+    fn indirect_error(&self, msg: &str, conf: &mut ShellCore) -> Vec<Vec<QuotedString>> {
+        eprintln!("bash: {}", msg);
+        conf.set_var("?", "1");
+        conf.eval_error = true;
+        single("")
+    }
+
+    /* a single value; None if it is unset */
+    fn eval_value(&mut self, value: Option<String>, conf: &mut ShellCore) -> Vec<Vec<QuotedString>> {
+        if ! self.empty_option.is_empty() {
+            let (unset, empty) = (value.is_none(), value.as_deref() == Some(""));
+            return match self.empty_treat(unset, empty, conf) {
+                Some(alt) => vec!(alt),
+                None      => single(&value.unwrap_or_default()),
+            };
+        }
+
+        let value = match value {
+            Some(v) => v,
+            None => {
+                if conf.has_flag('u') {
+                    self.unbound_error(conf);
+                }
+                /* attributes are shown even if the variable has no value */
+                if self.operator == "@a" {
+                    return single(&attributes(&self.name, conf));
+                }
+                return single("");
+            },
+        };
+
+        if self.operator.starts_with('@') {
+            return single(&self.transform(&value, conf));
+        }
+
+        if self.prefix == "#" {
+            let len = value.chars().count();
+            return single(&len.to_string());
+        }
+
+        if self.offset.is_some() {
+            let chars = value.chars().collect::<Vec<char>>();
+            let ans = match self.substring_range(chars.len(), conf) {
+                Some((start, end)) => chars[start..end].iter().collect(),
+                None => String::new(),
+            };
+            return single(&ans);
+        }
+
+        if self.pattern.is_some() {
+            return single(&self.apply_pattern(&value, conf));
+        }
+
+        single(&value)
+    }
+
+    /* elements of an array or positional parameters */
+    fn eval_list(&mut self, values: Vec<String>, star: bool, conf: &mut ShellCore) -> Vec<Vec<QuotedString>> {
+        if self.prefix == "#" {
+            return single(&values.len().to_string());
+        }
 
-        match opt {
-            "" => "".to_string(),
-            ":-" => self.empty_option_string.clone(),
-            ":=" => {
-                conf.set_var(&self.name, &self.empty_option_string);
-                self.empty_option_string.clone()
+        let mut values = match self.offset.is_some() {
+            true => match self.substring_range(values.len(), conf) {
+                Some((start, end)) => values[start..end].to_vec(),
+                None => vec![],
             },
-            ":?" => {
-                eprintln!("bash: {}: {}",self.name.clone(), self.empty_option_string.clone());
+            false => values,
+        };
+
+        if self.pattern.is_some() {
+            values = values.iter().map(|v| self.apply_pattern(v, conf)).collect();
+        }else if self.operator.starts_with('@') {
+            values = values.iter().map(|v| self.transform(v, conf)).collect();
+        }else if ! self.empty_option.is_empty() {
+            let (unset, empty) = (values.is_empty(), values.concat().is_empty());
+            if let Some(alt) = self.empty_treat(unset, empty, conf) {
+                return vec!(alt);
+            }
+        }
+
+        if star && conf.in_double_quot {
+            let sep = match conf.find_var("IFS") {
+                Some(ifs) => ifs.chars().next().map(|c| c.to_string()).unwrap_or_default(),
+                None      => " ".to_string(),
+            };
+            return single(&values.join(&sep));
+        }
+        vec!(values.iter().map(|v| QuotedString::unquoted(v)).collect())
+    }
+
+    fn eval_arithmetic(expr: &str, conf: &mut ShellCore) -> Result<i64, String> {
+        match expr.trim().is_empty() {
+            true  => Ok(0),
+            false => calculate(expr.to_string(), conf)?.parse::<i64>().map_err(|e| e.to_string()),
+        }
+    }
+
+    /* the range given by :offset:length; None if the range is empty */
+    fn substring_range(&mut self, len: usize, conf: &mut ShellCore) -> Option<(usize, usize)> {
+        let result = self.substring_range_result(len as i64, conf);
+        match result {
+            Ok(Some((start, end))) => Some((start as usize, end as usize)),
+            Ok(None) => None,
+            Err(msg) => {
+                eprintln!("bash: {}", msg);
                 conf.set_var("?", "1");
-                "".to_string()
+                conf.eval_error = true;
+                None
+            },
+        }
+    }
+
+    fn substring_range_result(&mut self, len: i64, conf: &mut ShellCore) -> Result<Option<(i64, i64)>, String> {
+        let offset = SubwordVariable::eval_arithmetic(self.offset.as_ref().unwrap(), conf)?;
+        let start = if offset < 0 { len + offset } else { offset };
+        if start < 0 || start > len {
+            return Ok(None);
+        }
+
+        let end = match &self.length {
+            None => len,
+            Some(expr) => match SubwordVariable::eval_arithmetic(expr, conf)? {
+                n if n < 0 && len + n < start => {
+                    return Err(format!("{}: substring expression < 0", expr.trim()));
+                },
+                n if n < 0 => len + n,
+                n => std::cmp::min(start.saturating_add(n), len),
+            },
+        };
+        Ok(Some((start, end)))
+    }
+
+    fn apply_pattern(&mut self, value: &str, conf: &mut ShellCore) -> String {
+        let pattern = self.pattern.as_mut().unwrap().eval_pattern(conf, GLOB_SPECIALS);
+        let replacement = match self.replacement.as_mut() {
+            Some(w) => w.eval_string(conf),
+            None    => String::new(),
+        };
+
+        match self.operator.as_str() {
+            "#" | "##" | "%" | "%%" => remove_match(value, &pattern, &self.operator),
+            "^" | "^^" | "," | ",," => modify_case(value, &pattern, &self.operator),
+            _ => replace_match(value, &pattern, &replacement, &self.operator),
+        }
+    }
+
+    /* ${name@op} */
+    fn transform(&self, value: &str, conf: &mut ShellCore) -> String {
+        let value = value.to_string();
+        match self.operator.as_str() {
+            "@Q" => quote(&value),
+            "@E" => unescape_ansi_c(&value),
+            "@P" => {
+                let prompt = expand_prompt(&value, conf);
+                Word::parse_double_quoted_expansions(&prompt, conf, false).eval_string(conf)
+            },
+            "@U" => value.to_uppercase(),
+            "@L" => value.to_lowercase(),
+            "@u" => {
+                let mut chars = value.chars();
+                match chars.next() {
+                    Some(c) => c.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            },
+            "@a" => attributes(&self.name, conf),
+            "@A" => match attributes(&self.name, conf) {
+                a if a.is_empty() => format!("{}={}", self.name, quote(&value)),
+                a => format!("declare -{} {}={}", a, self.name, quote(&value)),
+            },
+            _ => value,
+        }
+    }
+
+    fn eval_empty_option_word(&mut self, conf: &mut ShellCore) -> String {
+        match self.empty_option_word.as_mut() {
+            Some(w) => w.eval_string(conf),
+            None    => String::new(),
+        }
+    }
+
+    /* the word keeps its quoting and is split out of double quotes */
+    fn eval_empty_option_fields(&mut self, conf: &mut ShellCore) -> Vec<QuotedString> {
+        self.word_fields = true;
+        match (self.empty_option_word.as_mut(), self.split) {
+            (Some(w), true)  => w.eval(conf),
+            (Some(w), false) => vec!(QuotedString::join(&w.eval_without_split(conf), " ")),
+            (None, _) => vec!(QuotedString::default()),
+        }
+    }
+
+    /* the result of -, =, ?, + (and those with :) if it replaces the value */
+    fn empty_treat(&mut self, unset: bool, empty: bool, conf: &mut ShellCore) -> Option<Vec<QuotedString>> {
+        let colon = self.empty_option.starts_with(':');
+        let null = unset || (colon && empty);
+
+        match self.empty_option.trim_start_matches(':') {
+            "+" if null => Some(vec!(QuotedString::default())),
+            "+" => Some(self.eval_empty_option_fields(conf)),
+            _ if ! null => None,
+            "-" => Some(self.eval_empty_option_fields(conf)),
+            "=" => {
+                let value = self.eval_empty_option_word(conf);
+                conf.set_var(&self.name, &value);
+                Some(vec!(QuotedString::unquoted(&value)))
             },
-            _ => "".to_string(),
+            _ => {
+                let msg = match self.eval_empty_option_word(conf) {
+                    m if ! m.is_empty() => m,
+                    _ if colon => "parameter null or not set".to_string(),
+                    _ => "parameter not set".to_string(),
+                };
+                self.expansion_error(&format!("{}: {}", self.name, msg), conf);
+                Some(vec!(QuotedString::default()))
+            },
+        }
+    }
+
+    fn unbound_error(&self, conf: &mut ShellCore) {
+        let name = match self.name.parse::<usize>() {
+            Ok(_) => format!("${}", self.name),
+            Err(_) => self.name.clone(),
+        };
+        let sub = self.subscript.as_ref().map_or(String::new(), |s| s.text.clone());
+        self.expansion_error(&format!("{}{}: unbound variable", name, sub), conf);
+    }
+
+    /* a non-interactive shell exits */
+    fn expansion_error(&self, msg: &str, conf: &mut ShellCore) {
+        eprintln!("bash: {}", msg);
+        if ! conf.has_flag('i') {
+            process::exit(1);
         }
+        conf.set_var("?", "1");
+        conf.eval_error = true;
+    }
+
+    pub fn parse(text: &mut Feeder, conf: &mut ShellCore) -> Option<SubwordVariable> {
+        SubwordVariable::parse_quoting(text, conf, false)
     }
 
-    pub fn parse(text: &mut Feeder) -> Option<SubwordVariable> {
+    /* the word of ${name-word} follows the rules of double quotes */
+    pub fn parse_in_double_quot(text: &mut Feeder, conf: &mut ShellCore) -> Option<SubwordVariable> {
+        SubwordVariable::parse_quoting(text, conf, true)
+    }
+
+    fn parse_quoting(text: &mut Feeder, conf: &mut ShellCore, in_double_quot: bool) -> Option<SubwordVariable> {
         if text.starts_with("${") {
-            return SubwordVariable::parse_in_brace(text);
+            return SubwordVariable::parse_in_brace(text, conf, in_double_quot);
         }
         if ! text.starts_with("$") {
             return None;
@@ -81,24 +428,113 @@ impl SubwordVariable {
         Some(ans)
     }
     
-    fn parse_in_brace(text: &mut Feeder) -> Option<SubwordVariable> {
+    /* # before a name for the length, or ! for indirection and listing */
+    fn parse_prefix(text: &mut Feeder, ans: &mut SubwordVariable) {
+        if ! text.starts_with("#") && ! text.starts_with("!") {
+            return;
+        }
+
+        let mut after = text.clone();
+        after.consume(1);
+        let pos = after.scanner_name_or_parameter();
+        let next = after.chars_after(pos).next();
+        let is_length = next == Some('[') || next == Some('}');
+        if pos > 0 && (text.starts_with("!") || is_length) {
+            ans.prefix = text.consume(1);
+            ans.text += &ans.prefix.clone();
+        }
+    }
+
+    /* :offset and :offset:length */
+    fn parse_offset(text: &mut Feeder, ans: &mut SubwordVariable) {
+        ans.text += &text.consume(1);
+        let pos = text.scanner_expansion_arg(":}");
+        let offset = text.consume(pos);
+        ans.text += &offset;
+        ans.offset = Some(offset);
+
+        if text.starts_with(":") {
+            ans.text += &text.consume(1);
+            let pos = text.scanner_expansion_arg("}");
+            let length = text.consume(pos);
+            ans.text += &length;
+            ans.length = Some(length);
+        }
+    }
+
+    fn parse_pattern_word(text: &mut Feeder, conf: &mut ShellCore, ans: &mut SubwordVariable, ends: &str) -> Word {
+        let pos = text.scanner_expansion_arg(ends);
+        let s = text.consume(pos);
+        ans.text += &s;
+        Word::parse_expansions(&s, conf)
+    }
+
+    fn parse_empty_option_word(text: &mut Feeder, conf: &mut ShellCore, ans: &mut SubwordVariable, in_double_quot: bool) -> Word {
+        if ! in_double_quot {
+            return SubwordVariable::parse_pattern_word(text, conf, ans, "}");
+        }
+
+        let pos = text.scanner_expansion_arg("}");
+        let s = text.consume(pos);
+        ans.text += &s;
+        Word::parse_double_quoted_expansions(&s, conf, true)
+    }
+
+    /* #, ##, %, %%, ^, ^^, ",", ",," pattern and /, //, /#, /% pattern/string */
+    fn parse_pattern(text: &mut Feeder, conf: &mut ShellCore, ans: &mut SubwordVariable) {
+        let ops = ["##", "#", "%%", "%", "//", "/#", "/%", "/", "^^", "^", ",,", ","];
+        let op = match ops.iter().find(|op| text.starts_with(op)) {
+            Some(op) => op.to_string(),
+            None => return,
+        };
+        ans.text += &text.consume(op.len());
+
+        if ! op.starts_with('/') {
+            ans.pattern = Some(SubwordVariable::parse_pattern_word(text, conf, ans, "}"));
+            ans.operator = op;
+            return;
+        }
+
+        ans.pattern = Some(SubwordVariable::parse_pattern_word(text, conf, ans, "/}"));
+        if text.starts_with("/") {
+            ans.text += &text.consume(1);
+            ans.replacement = Some(SubwordVariable::parse_pattern_word(text, conf, ans, "}"));
+        }
+        ans.operator = op;
+    }
+
+    fn parse_in_brace(text: &mut Feeder, conf: &mut ShellCore, in_double_quot: bool) -> Option<SubwordVariable> {
         let mut ans = SubwordVariable::new(text);
         let backup = text.clone();
 
         ans.text = text.consume(2);
+        SubwordVariable::parse_prefix(text, &mut ans);
         
         let pos = text.scanner_name_or_parameter();
         ans.name = text.consume(pos);
         ans.text += &ans.name.clone();
 
-        if text.starts_with( ":-") || text.starts_with( ":=") 
-            || text.starts_with( ":?") || text.starts_with( ":+") {
-            ans.empty_option = text.consume(2);
-            ans.text += &ans.empty_option.clone();
+        if let Some(s) = Subscript::parse(text, conf) {
+            ans.text += &s.text;
+            ans.subscript = Some(s);
+        }else if ans.prefix == "!" && (text.starts_with("*}") || text.starts_with("@}")) {
+            ans.list_suffix = text.consume(1);
+            ans.text += &ans.list_suffix.clone();
+        }
 
-            let pos = text.scanner_until_escape("}");
-            ans.empty_option_string = text.consume(pos);
-            ans.text += &ans.empty_option_string.clone();
+        let transforms = ["@Q", "@E", "@P", "@A", "@a", "@U", "@u", "@L"];
+        let ops = [":-", ":=", ":?", ":+", "-", "=", "?", "+"];
+        if let Some(t) = transforms.iter().find(|t| text.starts_with(t)) {
+            ans.operator = text.consume(t.len());
+            ans.text += &ans.operator.clone();
+        }else if let Some(op) = ops.iter().find(|op| text.starts_with(op)) {
+            ans.empty_option = text.consume(op.len());
+            ans.text += &ans.empty_option.clone();
+            ans.empty_option_word = Some(SubwordVariable::parse_empty_option_word(text, conf, &mut ans, in_double_quot));
+        }else if text.starts_with(":") {
+            SubwordVariable::parse_offset(text, &mut ans);
+        }else{
+            SubwordVariable::parse_pattern(text, conf, &mut ans);
         }
 
         if text.starts_with("}") {
@@ -111,3 +547,171 @@ impl SubwordVariable {
         }
     }
 }
+
+fn single(s: &str) -> Vec<Vec<QuotedString>> {
+    vec!(vec!(QuotedString::unquoted(s)))
+}
+
+/* positions where a string can be split */
+fn boundaries(s: &str) -> Vec<usize> {
+    let mut ans = s.char_indices().map(|(i, _)| i).collect::<Vec<usize>>();
+    ans.push(s.len());
+    ans
+}
+
+/* # and ## remove a prefix, % and %% remove a suffix */
+fn remove_match(value: &str, pattern: &String, op: &str) -> String {
+    let mut points = boundaries(value);
+    if op == "##" || op == "%" {
+        points.reverse();
+    }
+
+    for p in points {
+        let (head, tail) = value.split_at(p);
+        match op {
+            "#" | "##" if glob_match(pattern, &head.to_string()) => return tail.to_string(),
+            "%" | "%%" if glob_match(pattern, &tail.to_string()) => return head.to_string(),
+            _ => {},
+        }
+    }
+    value.to_string()
+}
+
+/* the longest match from the leftmost position */
+fn longest_match(value: &str, pattern: &String, start: usize, anchored_end: bool) -> Option<usize> {
+    for end in boundaries(value).into_iter().rev().filter(|e| *e >= start) {
+        if glob_match(pattern, &value[start..end].to_string()) {
+            return Some(end);
+        }
+        if anchored_end {
+            break;
+        }
+    }
+    None
+}
+
+/* / replaces the first match, // all matches, /# a match at the head, /% a match at the tail */
+fn replace_match(value: &str, pattern: &String, replacement: &str, op: &str) -> String {
+    if pattern.is_empty() {
+        return match op {
+            "/#" => replacement.to_string() + value,
+            "/%" => value.to_string() + replacement,
+            _    => value.to_string(),
+        };
+    }
+
+    let mut ans = String::new();
+    let mut rest_from = 0;
+    for start in boundaries(value) {
+        if start < rest_from {
+            continue;
+        }
+        if op == "/#" && start > 0 {
+            break;
+        }
+
+        let end = match longest_match(value, pattern, start, op == "/%") {
+            Some(end) if end > start || op == "/#" || op == "/%" => end,
+            _ => continue,
+        };
+
+        ans += &value[rest_from..start];
+        ans += replacement;
+        rest_from = end;
+        if op != "//" {
+            break;
+        }
+    }
+    ans + &value[rest_from..]
+}
+
+/* ^ and , convert the first character, ^^ and ,, all characters matching the pattern */
+fn modify_case(value: &str, pattern: &String, op: &str) -> String {
+    let mut ans = String::new();
+    for (i, ch) in value.chars().enumerate() {
+        let target = (i == 0 || op.len() == 2)
+            && (pattern.is_empty() || glob_match(pattern, &ch.to_string()));
+
+        match (target, op.starts_with('^')) {
+            (true, true)  => ans.extend(ch.to_uppercase()),
+            (true, false) => ans.extend(ch.to_lowercase()),
+            _ => ans.push(ch),
+        }
+    }
+    ans
+}
+
+/* names of variables beginning with the prefix */
+fn var_names(prefix: &str, conf: &ShellCore) -> Vec<String> {
+    let mut names = conf.vars.keys()
+        .chain(conf.arrays.keys())
+        .chain(conf.assoc_arrays.keys())
+        .cloned()
+        .chain(std::env::vars().map(|(k, _)| k))
+        .filter(|n| n.starts_with(prefix))
+        .collect::<Vec<String>>();
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn attributes(name: &str, conf: &ShellCore) -> String {
+    let mut ans = String::new();
+    if conf.arrays.contains_key(name) {
+        ans.push('a');
+    }
+    if conf.is_assoc(name) {
+        ans.push('A');
+    }
+    if ! conf.vars.contains_key(name) && std::env::var(name).is_ok() {
+        ans.push('x');
+    }
+    ans
+}
+
+/* quoted so that the shell can read it again */
+fn quote(s: &str) -> String {
+    if ! s.chars().any(|c| c.is_control()) {
+        return format!("'{}'", s.replace('\'', "'\\''"));
+    }
+
+    let mut ans = "$'".to_string();
+    for c in s.chars() {
+        match c {
+            '\\' => ans += "\\\\",
+            '\'' => ans += "\\'",
+            '\n' => ans += "\\n",
+            '\t' => ans += "\\t",
+            '\r' => ans += "\\r",
+            '\x07' => ans += "\\a",
+            '\x1b' => ans += "\\E",
+            c if c.is_control() => ans += &format!("\\{:03o}", c as u32),
+            c => ans.push(c),
+        }
+    }
+    ans + "'"
+}
+
+/* declare -a name=([0]="..." ...) */
+fn declare_array_string(name: &str, conf: &ShellCore) -> String {
+    let dquote = |s: &String| {
+        let mut ans = String::new();
+        for c in s.chars() {
+            if "\"\\$`".contains(c) {
+                ans.push('\\');
+            }
+            ans.push(c);
+        }
+        ans
+    };
+
+    let elems = conf.get_array_indices(name).iter()
+        .zip(conf.get_array_values(name).iter())
+        .map(|(k, v)| format!("[{}]=\"{}\"", k, dquote(v)))
+        .collect::<Vec<String>>();
+
+    match conf.is_assoc(name) {
+        true  => format!("declare -A {}=({} )", name, elems.join(" ")),
+        false => format!("declare -a {}=({})", name, elems.join(" ")),
+    }
+}
@@ -8,6 +8,7 @@ use crate::Feeder;
 use crate::elements::subword;
 use crate::elements::subword::Subword;
 use crate::elements::subword::tilde::SubwordTildePrefix;
+use crate::elements::word::QuotedString;
 
 pub struct Value {
     pub text: String,
@@ -57,11 +58,11 @@ impl Value {
         }
     }
 
-    pub fn eval(&mut self, conf: &mut ShellCore) -> Vec<String> {
+    /* gives a raw string after the quote removal; fields such as those of "$@" are joined with spaces */
+    pub fn eval(&mut self, conf: &mut ShellCore) -> String {
         let mut subevals = vec![];
         for sa in &mut self.subvalues {
-            let vs = sa.eval(conf, false);
-            subevals.push(vs);
+            subevals.push(sa.eval(conf, false));
         }
 
         let mut strings = vec![];
@@ -70,11 +71,7 @@ impl Value {
             strings = combine(&mut strings, ss);
         }
 
-        let mut ans = vec![];
-        for v in strings {
-            ans.append(&mut v.clone());
-        }
-        ans
+        QuotedString::join(&strings.concat(), " ").to_string()
     }
 
 }
@@ -1,42 +1,131 @@
 //SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+use std::fmt;
+use std::ops::AddAssign;
 use crate::ShellCore;
-use crate::utils::combine;
+use crate::utils::{combine, split_fields, FIELD_BOUNDARY};
 use crate::debuginfo::DebugInfo;
 use crate::Feeder;
 use crate::elements::subword;
 use crate::elements::subword::Subword;
 use crate::elements::subword::tilde::SubwordTildePrefix;
 use crate::elements::subword::string_non_quoted::SubwordStringNonQuoted;
+use crate::elements::subword::string_double_quoted::SubwordStringDoubleQuoted;
 
-pub struct Word {
-    pub text: String,
-    pub pos: DebugInfo,
-    pub subwords: Vec<Box<dyn Subword>>,
+/* a string whose characters carry whether they are quoted */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QuotedString {
+    chars: Vec<(char, bool)>,
 }
 
-impl Word {
-    pub fn remove_escape(text: &String) -> String{
+impl QuotedString {
+    pub fn quoted(s: &str) -> QuotedString {
+        QuotedString { chars: s.chars().map(|c| (c, true)).collect() }
+    }
+
+    pub fn unquoted(s: &str) -> QuotedString {
+        QuotedString { chars: s.chars().map(|c| (c, false)).collect() }
+    }
+
+    /* unquoted text in which a backslash quotes the next character */
+    pub fn from_escaped(s: &str) -> QuotedString {
+        let mut ans = QuotedString::default();
+        let mut chars = s.chars();
+        while let Some(ch) = chars.next() {
+            if ch != '\\' {
+                ans.chars.push((ch, false));
+                continue;
+            }
+
+            match chars.next() {
+                Some(c) => ans.chars.push((c, true)),
+                None => ans.chars.push((ch, true)),
+            }
+        }
+        ans
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    pub fn to_quoted(&self) -> QuotedString {
+        QuotedString { chars: self.chars.iter().map(|(c, _)| (*c, true)).collect() }
+    }
+
+    pub fn replace(&self, from: char, to: char) -> QuotedString {
+        let chars = self.chars.iter()
+            .map(|(c, q)| if *c == from { (to, *q) } else { (*c, *q) })
+            .collect();
+        QuotedString { chars }
+    }
+
+    pub fn remove(&self, target: char) -> QuotedString {
+        QuotedString { chars: self.chars.iter().filter(|(c, _)| *c != target).cloned().collect() }
+    }
+
+    pub fn consists_of(&self, target: char) -> bool {
+        ! self.is_empty() && self.chars.iter().all(|(c, _)| *c == target)
+    }
+
+    /* true if a target is neither quoted nor escaped by a backslash from an expansion */
+    pub fn has_unquoted(&self, targets: &str) -> bool {
         let mut escaped = false;
-        let mut ans = "".to_string();
+        for (c, q) in &self.chars {
+            if ! escaped && ! q && targets.contains(*c) {
+                return true;
+            }
+            escaped = ! escaped && ! q && *c == '\\';
+        }
+        false
+    }
 
-        let deescape_twordet = |c: char| {
-            "$*\" \\`{};()^<>?[]'!".chars().any(|x| x == c)
-        };
-        
-        for ch in text.chars() {
-            if escaped || ch != '\\' {
-                if escaped && !deescape_twordet(ch) {
-                    ans.push('\\');
-                }
-                ans.push(ch);
-            };
-            escaped = !escaped && ch == '\\';
+    /* quoted characters listed in specials are escaped by backslashes so that they lose their meaning;
+       a backslash from an unquoted part is kept as an escape */
+    pub fn to_pattern(&self, specials: &str) -> String {
+        let mut ans = String::new();
+        for (c, q) in &self.chars {
+            if *q && specials.contains(*c) {
+                ans.push('\\');
+            }
+            ans.push(*c);
+        }
+        ans
+    }
+
+    pub fn join(fields: &[QuotedString], sep: &str) -> QuotedString {
+        let mut ans = QuotedString::default();
+        for (i, f) in fields.iter().enumerate() {
+            if i > 0 {
+                ans += &QuotedString::unquoted(sep);
+            }
+            ans += f;
         }
         ans
     }
+}
+
+/* quote removal */
+impl fmt::Display for QuotedString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.chars.iter().map(|(c, _)| c).collect::<String>())
+    }
+}
+
+impl AddAssign<&QuotedString> for QuotedString {
+    fn add_assign(&mut self, other: &QuotedString) {
+        self.chars.extend(other.chars.iter().cloned());
+    }
+}
 
+pub struct Word {
+    pub text: String,
+    pub pos: DebugInfo,
+    pub subwords: Vec<Box<dyn Subword>>,
+}
+
+impl Word {
     // single quoted word or double quoted word or non quoted word 
     pub fn parse(text: &mut Feeder, conf: &mut ShellCore, is_in_brace: bool) -> Option<Word> {
         if text.len() == 0 {
@@ -70,6 +159,61 @@ impl Word {
         }
     }
 
+    /* only expansions and quotes are parsed; the other characters are kept for arithmetic */
+    pub fn parse_expansions(inner: &str, conf: &mut ShellCore) -> Word {
+        let mut f = Feeder::new_from(inner.to_string());
+        let mut ans = Word {
+            text: inner.to_string(),
+            pos: DebugInfo::init(&f),
+            subwords: vec![],
+        };
+
+        while f.len() > 0 {
+            if f.starts_with("$") || f.starts_with("\"") || f.starts_with("'") {
+                if let Some(sw) = subword::parse_in_arg(&mut f, conf, false) {
+                    ans.subwords.push(sw);
+                    continue;
+                }
+            }
+
+            let pos = match f.scanner_until(0, "$\"'") {
+                0 => f.nth(0).len_utf8(),
+                n => n,
+            };
+            let s = f.consume(pos);
+            ans.subwords.push(Box::new(SubwordStringNonQuoted{text: s, pos: DebugInfo::init(&f)}));
+        }
+        ans
+    }
+
+    /* the same as parse_expansions but the other characters follow the rules of double quotes;
+       a double quote is literal unless quoted_parts */
+    pub fn parse_double_quoted_expansions(inner: &str, conf: &mut ShellCore, quoted_parts: bool) -> Word {
+        let mut f = Feeder::new_from(inner.to_string());
+        let mut ans = Word {
+            text: inner.to_string(),
+            pos: DebugInfo::init(&f),
+            subwords: vec![],
+        };
+
+        while f.len() > 0 {
+            if f.starts_with("$") || (quoted_parts && f.starts_with("\"")) || f.starts_with("`") {
+                if let Some(sw) = subword::parse_in_arg(&mut f, conf, false) {
+                    ans.subwords.push(sw);
+                    continue;
+                }
+            }
+
+            let pos = match f.scanner_double_quoted_word() {
+                0 => f.nth(0).len_utf8(),
+                n => n,
+            };
+            let s = f.consume(pos);
+            ans.subwords.push(Box::new(SubwordStringDoubleQuoted{text: s, pos: DebugInfo::init(&f)}));
+        }
+        ans
+    }
+
     pub fn parse_info(&self) -> Vec<String> {
         let mut ans = vec!(format!("    word      : '{}' ({})",
                               self.text.clone(), self.pos.get_text()));
@@ -80,17 +224,52 @@ impl Word {
         ans
     }
 
-    pub fn eval(&mut self, conf: &mut ShellCore) -> Vec<String> {
+    pub fn eval(&mut self, conf: &mut ShellCore) -> Vec<QuotedString> {
+        self.eval_fields(conf, true)
+    }
+
+    /* for contexts without field splitting: assignments, case, [[ ]], redirects */
+    pub fn eval_without_split(&mut self, conf: &mut ShellCore) -> Vec<QuotedString> {
+        self.eval_fields(conf, false)
+    }
+
+    /* a string after the quote removal */
+    pub fn eval_string(&mut self, conf: &mut ShellCore) -> String {
+        QuotedString::join(&self.eval_without_split(conf), " ").to_string()
+    }
+
+    /* a pattern in which quoted characters listed in specials lose their meaning */
+    pub fn eval_pattern(&mut self, conf: &mut ShellCore, specials: &str) -> String {
+        QuotedString::join(&self.eval_without_split(conf), " ").to_pattern(specials)
+    }
+
+    fn eval_fields(&mut self, conf: &mut ShellCore, split: bool) -> Vec<QuotedString> {
+        self.eval_alternatives(conf, split).concat().into_iter()
+            .filter(|s| ! s.consists_of(FIELD_BOUNDARY))
+            .map(|s| s.remove(FIELD_BOUNDARY))
+            .collect()
+    }
+
+    /* the fields for each alternative of brace expansion */
+    pub fn eval_alternatives(&mut self, conf: &mut ShellCore, split: bool) -> Vec<Vec<QuotedString>> {
         let mut subevals = vec![];
         for sa in &mut self.subwords {
-            let vs = sa.eval(conf, true);
+            let vs = sa.eval(conf, split);
 
             let mut cvs = vec![];
-            if sa.permit_lf() {
+            if sa.split_by_ifs() && split {
+                let ifs = conf.find_var("IFS").unwrap_or(" \t\n".to_string());
+                for v in vs {
+                    cvs.push(v.iter()
+                        .flat_map(|s| split_fields(&s.to_string(), &ifs))
+                        .map(|s| QuotedString::unquoted(&s))
+                        .collect());
+                }
+            }else if sa.permit_lf() || sa.split_by_ifs() {
                 cvs = vs;
             }else{
                 for v in vs {
-                    let cv = v.iter().map(|s| s.replace("\n", " ")).collect();
+                    let cv = v.iter().map(|s| s.replace('\n', ' ')).collect();
                     cvs.push(cv);
                 }
             }
@@ -103,12 +282,7 @@ impl Word {
         for ss in subevals {
             strings = combine(&mut strings, ss);
         }
-
-        let mut ans = vec![];
-        for v in strings {
-            ans.append(&mut v.clone());
-        }
-        ans
+        strings
     }
 
     pub fn get_text(&self) -> String { self.text.clone() }
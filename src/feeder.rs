@@ -16,6 +16,7 @@ pub struct Feeder {
     from_lineno: u32,
     to_lineno: u32,
     pos_in_line: u32,
+    here_doc_lines: String, // lines of here-documents cut out of the current line
 }
 
 impl Feeder {
@@ -25,6 +26,7 @@ impl Feeder {
             from_lineno: 0,
             to_lineno: 0,
             pos_in_line: 0,
+            here_doc_lines: String::new(),
         }
     }
 
@@ -76,16 +78,70 @@ impl Feeder {
         self.from_lineno = backup.from_lineno;
         self.to_lineno = backup.to_lineno;
         self.pos_in_line = backup.pos_in_line;
+        self.here_doc_lines = backup.here_doc_lines.clone();
     }
 
     pub fn consume(&mut self, cutpos: usize) -> String {
-        let cut = self.remaining[0..cutpos].to_string();
+        let mut cut = self.remaining[0..cutpos].to_string();
         self.pos_in_line += cutpos as u32;
         self.remaining = self.remaining[cutpos..].to_string();
 
+        /* the lines of here-documents come back to the text after the end of the line */
+        if self.here_doc_lines.len() > 0 {
+            if let Some(p) = cut.find('\n') {
+                cut.insert_str(p+1, &self.here_doc_lines);
+                self.here_doc_lines.clear();
+            }
+        }
+
         cut
     }
 
+    /* cuts the lines of a here-document out from the next line and gives them without the delimiter */
+    pub fn cut_here_document(&mut self, delimiter: &str, strip_tabs: bool, core: &mut ShellCore) -> Vec<String> {
+        while ! self.remaining.contains('\n') {
+            if ! self.feed_additional_line(core) {
+                self.remaining.push('\n');
+            }
+        }
+
+        let start = self.remaining.find('\n').unwrap() + 1;
+        let mut end = start;
+        let mut ans = vec![];
+        loop {
+            let len = self.remaining[end..].find('\n');
+            let mut line = match len {
+                Some(n) => self.remaining[end..end+n].to_string(),
+                None => self.remaining[end..].to_string(),
+            };
+            if strip_tabs {
+                line = line.trim_start_matches('\t').to_string();
+            }
+
+            if line == delimiter {
+                end += len.map_or(line.len(), |n| n + 1);
+                break;
+            }
+
+            if let Some(n) = len {
+                end += n + 1;
+                ans.push(line + "\n");
+            }else if ! self.feed_additional_line(core) {
+                eprintln!("bash: warning: here-document at line {} delimited by end-of-file (wanted `{}')",
+                          self.from_lineno, delimiter);
+                if line.len() > 0 {
+                    ans.push(line);
+                }
+                end = self.remaining.len();
+                break;
+            }
+        }
+
+        self.here_doc_lines += &self.remaining[start..end];
+        self.remaining.replace_range(start..end, "");
+        ans
+    }
+
     pub fn consume_blank(&mut self) -> String {
         let d = self.scanner_blank();
         self.consume(d)
@@ -128,7 +184,7 @@ impl Feeder {
     pub fn feed_additional_line(&mut self, core: &mut ShellCore) -> bool {
         //let ret = if core.flags.i {
         let ret = if core.has_flag('i') {
-            let len_prompt = term::prompt_additional();
+            let len_prompt = term::prompt_additional(core);
             if let Some(s) = term::read_line_terminal(len_prompt, core){
                 Some(s)
             }else {
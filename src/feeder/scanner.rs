@@ -19,6 +19,67 @@ impl Feeder {
         pos
     }
     
+    /* an argument in ${...} such as a pattern; quotes and nested expansions are skipped */
+    pub fn scanner_expansion_arg(&mut self, to: &str) -> usize {
+        let mut pos = 0;
+        let mut escaped = false;
+        let mut quote = None;
+        let mut depth = 0;
+        let mut prev = ' ';
+        for ch in self.chars_after(0) {
+            if escaped {
+                escaped = false;
+            }else if ch == '\\' && quote != Some('\'') {
+                escaped = true;
+            }else if let Some(q) = quote {
+                if ch == q {
+                    quote = None;
+                }
+            }else if ch == '\'' || ch == '"' {
+                quote = Some(ch);
+            }else if prev == '$' && (ch == '{' || ch == '(') {
+                depth += 1;
+            }else if depth > 0 && (ch == '}' || ch == ')') {
+                depth -= 1;
+            }else if depth == 0 && to.contains(ch) {
+                break;
+            }
+            prev = ch;
+            pos += ch.len_utf8();
+        }
+        pos
+    }
+
+    /* the position of the closing quote of $'...' */
+    pub fn scanner_ansi_c_quoted(&mut self) -> usize {
+        let mut pos = 2;
+        let mut escaped = false;
+        for ch in self.chars_after(2) {
+            if escaped || ch == '\\' {
+                escaped = !escaped;
+            }else if ch == '\'' {
+                break;
+            };
+            pos += ch.len_utf8();
+        }
+        pos
+    }
+
+    /* the position of the closing backquote */
+    pub fn scanner_backquoted(&mut self) -> usize {
+        let mut pos = 1;
+        let mut escaped = false;
+        for ch in self.chars_after(1) {
+            if escaped || ch == '\\' {
+                escaped = !escaped;
+            }else if ch == '`' {
+                break;
+            };
+            pos += ch.len_utf8();
+        }
+        pos
+    }
+
     pub fn scanner_until(&mut self, from: usize, to: &str) -> usize {
         let mut pos = from;
         for ch in self.chars_after(from) {
@@ -68,7 +129,7 @@ impl Feeder {
             }
 
             /* stop at meta characters, \n, quotes, start of brace, start of expansion*/
-            if let Some(_) = "|&;()<> \t\n\"'$".find(ch) {
+            if let Some(_) = "|&;()<> \t\n\"'$`".find(ch) {
                 break;
             }
             if ! ignore_brace && ch == '{' {
@@ -100,8 +161,35 @@ impl Feeder {
                 continue;
             }
 
-            /* stop at double quote or $*/
-            if let Some(_) = "\"$".find(ch) {
+            /* stop at double quote, $ or backquote */
+            if let Some(_) = "\"$`".find(ch) {
+                break;
+            }
+
+            pos += ch.len_utf8();
+        }
+
+        pos
+    }
+
+    pub fn scanner_here_document_string(&mut self) -> usize {
+        let mut escaped = false;
+        let mut pos = 0;
+        for ch in self.remaining.chars() {
+            if escaped {
+                escaped = false;
+                pos += ch.len_utf8();
+                continue;
+            }
+
+            if ch == '\\' {
+                escaped = true;
+                pos += ch.len_utf8();
+                continue;
+            }
+
+            /* stop at $ or backquote */
+            if ch == '$' || ch == '`' {
                 break;
             }
 
@@ -231,39 +319,54 @@ impl Feeder {
         (0 , None)
     }
 
-    pub fn scanner_comment(&mut self) -> usize {
-        if self.starts_with("#") {
-            return self.scanner_until(0, "\n");
+    /* returns the position of the closing "))" */
+    pub fn scanner_double_paren_end(&mut self) -> Option<usize> {
+        let mut depth = 0;
+        let mut pos = 0;
+        let mut closing = None;
+        for ch in self.chars_after(0) {
+            match ch {
+                '(' => {
+                    depth += 1;
+                    closing = None;
+                },
+                ')' if depth > 0 => {
+                    depth -= 1;
+                    closing = None;
+                },
+                ')' => {
+                    if closing.is_some() {
+                        return closing;
+                    }
+                    closing = Some(pos);
+                },
+                _ => closing = None,
+            }
+            pos += ch.len_utf8();
         }
-    
-        0
+        None
     }
 
-    pub fn scanner_integer(&mut self) -> usize {
-        if self.len() == 0 {
-            return 0;
-        }
-    
-        let mut pos = 0;
-        let mut minus = false;
-        if self.starts_with("-") {
-            pos += 1;
-            minus = true;
-        }
-    
-        for ch in self.chars_after(pos) {
-            if ch < '0' || ch > '9' {
-                break;
+    /* true if a ) closes the paren before the text ends */
+    pub fn scanner_single_paren_end(&mut self) -> bool {
+        let mut depth = 0;
+        for ch in self.chars_after(0) {
+            match ch {
+                '(' => depth += 1,
+                ')' if depth == 0 => return true,
+                ')' => depth -= 1,
+                _ => {},
             }
-    
-            pos += 1;
         }
-    
-        if minus && pos == 1 {
-            0
-        }else{
-            pos
+        false
+    }
+
+    pub fn scanner_comment(&mut self) -> usize {
+        if self.starts_with("#") {
+            return self.scanner_until(0, "\n");
         }
+    
+        0
     }
 
     fn scanner_parameter(&mut self, from: usize) -> usize {
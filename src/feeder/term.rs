@@ -17,7 +17,7 @@ use termion::input::TermRead;
 use crate::ShellCore;
 use completion::*;
 
-use crate::utils::chars_to_string;
+use crate::utils::{chars_to_string, expand_prompt};
 
 extern crate unicode_width;
 use unicode_width::UnicodeWidthStr;
@@ -338,10 +338,11 @@ impl Writer {
     }
 }
 
-pub fn prompt_additional() -> u16 {
-    print!("> ");
+pub fn prompt_additional(core: &mut ShellCore) -> u16 {
+    let prompt = expand_prompt(&core.get_var("PS2"), core);
+    print!("{}", prompt);
     io::stdout().flush().unwrap();
-    return 2;
+    chars_to_width(&prompt.chars().collect()) as u16
 }
 
 pub fn prompt_normal(core: &mut ShellCore) -> u16 {
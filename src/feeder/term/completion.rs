@@ -5,6 +5,7 @@ use std::io::Write;
 use std::collections::HashSet;
 
 use crate::ShellCore;
+use crate::elements::word::QuotedString;
 use crate::utils::{eval_glob, search_commands, expand_tilde};
 use crate::feeder::term::Writer;
 use crate::feeder::term::prompt_normal;
@@ -40,7 +41,7 @@ pub fn file_completion(writer: &mut Writer){
     let s: String = writer.last_word().replace("\\", "") + "*";
     let (s, home, org) = expand_tilde(&s);
 
-    let ans = eval_glob(&s.replace("\\", ""));
+    let ans = eval_glob(&QuotedString::unquoted(&s.replace("\\", "")));
     if ans.len() == 0 {
         return;
     };
@@ -104,7 +105,7 @@ pub fn show_file_candidates(writer: &mut Writer, core: &mut ShellCore) {
     let s: String = writer.last_word().replace("\\", "") + "*";
     let (s, _, _) = expand_tilde(&s);
 
-    let ans = eval_glob(&s);
+    let ans = eval_glob(&QuotedString::unquoted(&s));
     if ans.len() == 0 {
         return;
     };
@@ -1,7 +1,8 @@
 //SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-use nix::unistd::{close, dup2};
+use nix::unistd::{close, dup2, fork, pipe, write, ForkResult};
+use std::process::exit;
 use std::os::unix::prelude::RawFd;
 use crate::elements::redirect::Redirect;
 use crate::operators::RedirectOp;
@@ -80,6 +81,20 @@ impl FileDescs {
             }else{
                 panic!("Cannot open the file: {}", path);
             };
+        }else if r.redirect_type == RedirectOp::HereDoc /*"<<"*/ {
+            let (recv, send) = pipe().expect("Cannot open a pipe");
+            match unsafe{fork()} {
+                Ok(ForkResult::Child) => { // writes the document so as not to be blocked by a full pipe
+                    close(recv).expect("Cannot close in-pipe");
+                    let _ = write(send, path.as_bytes());
+                    exit(0);
+                },
+                Ok(ForkResult::Parent { .. }) => {
+                    close(send).expect("Cannot close out-pipe");
+                    Self::dup_and_close(recv, r.left_fd);
+                },
+                Err(err) => panic!("Failed to fork. {}", err),
+            }
         }else if r.redirect_type == RedirectOp::Input /*"<"*/ {
             if let Ok(file) = OpenOptions::new().read(true).open(&path){
                 Self::dup_and_close(file.into_raw_fd(), r.left_fd);
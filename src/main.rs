@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 mod bash_glob;
+mod bash_regex;
 mod calculator;
 mod elements;
 mod operators;
@@ -114,7 +115,7 @@ fn main() {
         }
     }
 
-    for f in [ "d", "v", "x" ] {
+    for f in [ "d", "u", "v", "x" ] {
         if words.iter().any(|a| has_option(a, f.to_string())) {
             core.flags += f;
         }
@@ -123,6 +124,7 @@ fn main() {
     let pid = process::id();
     core.set_var("$", &pid.to_string());
     core.set_var("IFS", " \t\n");
+    core.set_var("PS2", "> ");
     core.set_var("HOSTNAME", &get_hostname());
     core.set_var("SHELL", "rustybash");
     core.set_var("BASH", &core.args[0].to_string());
@@ -4,39 +4,44 @@
 use glob::glob;
 use crate::env;
 use std::fs;
+use std::ops::AddAssign;
 use std::io::{BufRead, BufReader};
 use std::fs::OpenOptions;
 use crate::ShellCore;
+use crate::bash_glob::GLOB_SPECIALS;
+use crate::elements::word::QuotedString;
 
 pub fn chars_to_string(chars: &Vec<char>) -> String {
     chars.iter().collect::<String>()
 }
 
-fn is_glob(s: &String) -> bool {
-    let mut escaped = false;
-
-    for ch in s.chars() {
-        if escaped {
-            continue;
-        }else if ! escaped && ch == '\\' {
-            escaped = true;
+/* the glob crate quotes a special character with brackets instead of a backslash */
+fn to_glob_pattern(s: &str) -> String {
+    let mut ans = String::new();
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            ans.push(ch);
             continue;
         }
 
-        if ch == '*' || ch == '[' || ch == '?' {
-            return true;
+        match chars.next() {
+            Some(c) if "*?[]".contains(c) => ans += &format!("[{}]", c),
+            Some(c) => ans.push(c),
+            None => ans.push('\\'),
         }
     }
-    return false;
+    ans
 }
 
-pub fn eval_glob(globstr: &String) -> Vec<String> {
-    if ! is_glob(&globstr) {
-        return vec!(globstr.clone());
+/* pathname expansion of a field; the field is given back after the quote removal if nothing matches */
+pub fn eval_glob(globstr: &QuotedString) -> Vec<String> {
+    if ! globstr.has_unquoted("*?[") {
+        return vec!(globstr.to_string());
     }
 
     let mut ans = vec![];
-    let g = globstr.replace("\\ ", " ").to_string();
+    let g = to_glob_pattern(&globstr.to_pattern(GLOB_SPECIALS));
 
     //TODO: too ugly
     if let Ok(path) = glob(&g) {
@@ -55,7 +60,7 @@ pub fn eval_glob(globstr: &String) -> Vec<String> {
     };
 
     if ans.len() == 0 {
-        return vec!(globstr.clone());
+        return vec!(globstr.to_string());
     }
 
     ans
@@ -110,7 +115,7 @@ pub fn search_aliases(head: &String, core: &ShellCore) -> Vec<String> {
     ans
 }
 
-pub fn combine_with(left: &Vec<String>, right: &Vec<String>, ch: &str) -> Vec<String> {
+pub fn combine_with<T: Clone + for<'a> AddAssign<&'a T>>(left: &Vec<T>, right: &Vec<T>, ch: &T) -> Vec<T> {
     if left.len() == 0 {
         return right.clone();
     };
@@ -119,7 +124,12 @@ pub fn combine_with(left: &Vec<String>, right: &Vec<String>, ch: &str) -> Vec<St
     for lstr in left {
         let mut con = right
             .iter()
-            .map(|r| lstr.clone() + ch + &r.clone())
+            .map(|r| {
+                let mut s = lstr.clone();
+                s += ch;
+                s += r;
+                s
+            })
             .collect();
 
         ans.append(&mut con);
@@ -127,7 +137,7 @@ pub fn combine_with(left: &Vec<String>, right: &Vec<String>, ch: &str) -> Vec<St
     ans
 }
 
-pub fn combine(left: &mut Vec<Vec<String>>, right: Vec<Vec<String>>) -> Vec<Vec<String>> {
+pub fn combine<T: Clone + for<'a> AddAssign<&'a T>>(left: &mut Vec<Vec<T>>, right: Vec<Vec<T>>) -> Vec<Vec<T>> {
     if left.len() == 0 {
         return right;
     };
@@ -136,6 +146,13 @@ pub fn combine(left: &mut Vec<Vec<String>>, right: Vec<Vec<String>>) -> Vec<Vec<
     for lv in left {
         let lv_len = lv.len();
         for rv in &right {
+            if lv_len == 0 || rv.is_empty() { /* e.g. "${a[@]}" of an empty array */
+                let mut clv = lv.clone();
+                clv.append(&mut rv.clone());
+                ans.push(clv);
+                continue;
+            }
+
             let mut clv = lv.clone();
             clv.append(&mut rv.clone());
             let n = clv[lv_len].clone();
@@ -147,6 +164,59 @@ pub fn combine(left: &mut Vec<Vec<String>>, right: Vec<Vec<String>>) -> Vec<Vec<
     ans
 }
 
+/* marks a field end produced by a delimiter at an edge of an expansion;
+   it keeps the expansion from being joined with the adjacent text and is removed later */
+pub const FIELD_BOUNDARY: char = '\u{FDD0}';
+
+/* field splitting of an expansion result with IFS */
+pub fn split_fields(s: &str, ifs: &str) -> Vec<String> {
+    if ifs.is_empty() {
+        return match s.is_empty() {
+            true  => vec![],
+            false => vec![s.to_string()],
+        };
+    }
+
+    let is_ifs_space = |c: char| " \t\n".contains(c) && ifs.contains(c);
+    let chars = s.chars().collect::<Vec<char>>();
+    let mut ans = vec![];
+    let mut field = String::new();
+    let mut i = 0;
+    let mut delimited = false;
+
+    while i < chars.len() {
+        if ! ifs.contains(chars[i]) {
+            field.push(chars[i]);
+            delimited = false;
+            i += 1;
+            continue;
+        }
+
+        let mut non_space = false;
+        while i < chars.len() && is_ifs_space(chars[i]) { i += 1; }
+        if i < chars.len() && ifs.contains(chars[i]) {
+            non_space = true;
+            i += 1;
+            while i < chars.len() && is_ifs_space(chars[i]) { i += 1; }
+        }
+
+        if ans.is_empty() && field.is_empty() && ! non_space {
+            ans.push(FIELD_BOUNDARY.to_string());
+        }else{
+            ans.push(field.clone());
+        }
+        field.clear();
+        delimited = true;
+    }
+
+    if ! field.is_empty() {
+        ans.push(field);
+    }else if delimited {
+        ans.push(FIELD_BOUNDARY.to_string());
+    }
+    ans
+}
+
 pub fn blue_strings(strings: &Vec<String>) -> Vec<String> {
     strings
         .iter()
@@ -291,3 +361,117 @@ pub fn get_fullpath(com: &String) -> String {
 
     "".to_string()
 }
+
+fn push_char(bytes: &mut Vec<u8>, c: char) {
+    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+/* backslash escapes in $'...'; \xHH and octal escapes give bytes, which are decoded as UTF-8 */
+pub fn unescape_ansi_c(s: &str) -> String {
+    let mut ans: Vec<u8> = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            push_char(&mut ans, ch);
+            continue;
+        }
+
+        let c = match chars.next() {
+            Some(c) => c,
+            None => {
+                push_char(&mut ans, '\\');
+                break;
+            },
+        };
+
+        let (radix, max_len) = match c {
+            'x' => (16, 2),
+            'u' => (16, 4),
+            'U' => (16, 8),
+            '0'..='7' => (8, 3),
+            _ => (0, 0),
+        };
+
+        if radix != 0 {
+            let mut digits = if radix == 8 { c.to_string() } else { String::new() };
+            while digits.len() < max_len {
+                match chars.peek() {
+                    Some(d) if d.is_digit(radix) => digits.push(chars.next().unwrap()),
+                    _ => break,
+                }
+            }
+
+            match (c, u32::from_str_radix(&digits, radix).ok()) {
+                ('u', Some(n)) | ('U', Some(n)) if char::from_u32(n).is_some() => {
+                    push_char(&mut ans, char::from_u32(n).unwrap());
+                },
+                ('u', _) | ('U', _) | (_, None) => {
+                    push_char(&mut ans, '\\');
+                    push_char(&mut ans, c);
+                },
+                (_, Some(n)) => ans.push(n as u8),
+            }
+            continue;
+        }
+
+        match c {
+            'a'  => push_char(&mut ans, '\x07'),
+            'b'  => push_char(&mut ans, '\x08'),
+            'e' | 'E' => push_char(&mut ans, '\x1b'),
+            'f'  => push_char(&mut ans, '\x0c'),
+            'n'  => push_char(&mut ans, '\n'),
+            'r'  => push_char(&mut ans, '\r'),
+            't'  => push_char(&mut ans, '\t'),
+            'v'  => push_char(&mut ans, '\x0b'),
+            '\\' | '\'' | '"' | '?' => push_char(&mut ans, c),
+            'c' => match chars.next() {
+                Some(ctrl) => push_char(&mut ans, ((ctrl.to_ascii_uppercase() as u8) ^ 0x40) as char),
+                None => ans.extend_from_slice(b"\\c"),
+            },
+            _ => {
+                push_char(&mut ans, '\\');
+                push_char(&mut ans, c);
+            },
+        }
+    }
+    String::from_utf8_lossy(&ans).to_string()
+}
+
+/* backslash escapes in prompt strings such as PS1 */
+pub fn expand_prompt(s: &str, core: &ShellCore) -> String {
+    let home = env::var("HOME").unwrap_or_default();
+    let cwd = env::current_dir().map(|p| p.display().to_string()).unwrap_or_default();
+    let host = core.get_var("HOSTNAME");
+
+    let mut ans = String::new();
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            ans.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('u') => ans += &env::var("USER").unwrap_or_default(),
+            Some('h') => ans += host.split('.').next().unwrap_or(""),
+            Some('H') => ans += &host,
+            Some('w') if ! home.is_empty() && cwd.starts_with(&home) => ans += &cwd.replacen(&home, "~", 1),
+            Some('w') => ans += &cwd,
+            Some('W') => ans += cwd.rsplit('/').next().filter(|b| ! b.is_empty()).unwrap_or("/"),
+            Some('$') => ans.push(if nix::unistd::geteuid().is_root() { '#' } else { '$' }),
+            Some('s') => ans += "bash",
+            Some('n') => ans.push('\n'),
+            Some('a') => ans.push('\x07'),
+            Some('e') => ans.push('\x1b'),
+            Some('\\') => ans.push('\\'),
+            Some('[') | Some(']') => {},
+            Some(c) => {
+                ans.push('\\');
+                ans.push(c);
+            },
+            None => ans.push('\\'),
+        }
+    }
+    ans
+}
@@ -58,5 +58,64 @@ res=$($com <<< 'eval "echo $(echo a b c);" "echo $(echo a b c)"' )
 [ "$res" = "a b c
 a b c" ] || err $LINENO
 
+# break and continue
+
+res=$($com <<< 'while true ; do echo a ; break ; done')
+[ "$res" = "a" ] || err $LINENO
+
+res=$($com <<< 'for i in 1 2 3 ; do if [ $i = 2 ] ; then continue ; fi ; echo $i ; done')
+[ "$res" = "1
+3" ] || err $LINENO
+
+res=$($com <<< 'for i in 1 2 ; do for j in a b ; do echo $i$j ; break 2 ; done ; done')
+[ "$res" = "1a" ] || err $LINENO
+
+res=$($com <<< 'for i in 1 2 ; do for j in a b ; do continue 2 ; echo no ; done ; echo $i ; done ; echo end')
+[ "$res" = "end" ] || err $LINENO
+
+res=$($com <<< 'for i in 1 2 ; do break 5 ; done ; echo $i')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< 'break ; echo $?' 2>&1)
+[ "$res" = "bash: break: only meaningful in a \`for', \`while', or \`until' loop
+0" ] || err $LINENO
+
+res=$($com <<< 'f () { break ; } ; for i in 1 2 ; do f ; echo $i ; done' 2> /dev/null)
+[ "$res" = "1
+2" ] || err $LINENO
+
+# let
+
+res=$($com <<< 'y=3; let "x=y+1" "z = x * 2"; echo $x $z')
+[ "$res" = "4 8" ] || err $LINENO
+
+res=$($com <<< 'let 1 0; echo $?; let 0 1; echo $?; let; echo $?')
+[ "$res" = "1
+0
+1" ] || err $LINENO
+
+res=$($com <<< 'i=0; let i++ i++; echo $i')
+[ "$res" = "2" ] || err $LINENO
+
+# set
+
+res=$($com <<< 'set a b c; echo $# $1 $3; set --; echo $#; set -- -x y; echo $1 $2')
+[ "$res" = "3 a c
+0
+-x y" ] || err $LINENO
+
+res=$($com <<< 'set -o nosuchoption; echo $?; set -q; echo $?' 2>/dev/null)
+[ "$res" = "1
+2" ] || err $LINENO
+
+# unset
+
+res=$($com <<< 'A=a ; f () { echo f ; } ; unset A ; unset -f f ; echo "[$A]" ; f')
+[ "$res" = "[]" ] || err $LINENO
+
+res=$($com <<< 'a=(1 2 3) ; unset "a[0]" "a[-1]" ; echo ${a[@]} ; unset "a[@]" ; echo "${#a[@]}"')
+[ "$res" = "2
+0" ] || err $LINENO
+
 
 echo OK $0
@@ -39,7 +39,7 @@ EOF
 [ "$res" = '" \ a  bc' ] || err $LINENO
 
 res=$($com <<< 'echo "\a\n\$\`\{\}"')
-[ "$res" = '\a\n$`{}' ] || err $LINENO
+[ "$res" = '\a\n$`\{\}' ] || err $LINENO
 
 res=$($com << 'EOF'
 echo "a'b'c"
@@ -121,6 +121,15 @@ res=$($com <<< 'echo a{b{}')
 res=$($com <<< 'echo }')
 [ "$res" = '}' ] || err $LINENO
 
+res=$($com <<< 'echo {1..5} {e..a} {01..10..3} {10..1..-4} {-05..05..5}')
+[ "$res" = '1 2 3 4 5 e d c b a 01 04 07 10 10 6 2 -05 000 005' ] || err $LINENO
+
+res=$($com <<< 'echo h{00..02}.x {a,{1..2}}{A..B}')
+[ "$res" = 'h00.x h01.x h02.x aA aB 1A 1B 2A 2B' ] || err $LINENO
+
+res=$($com <<< 'echo {1..a} {1..2..a} {ab..c} {1..} {1..2..3..4} "{1..2}"')
+[ "$res" = '{1..a} {1..2..a} {ab..c} {1..} {1..2..3..4} {1..2}' ] || err $LINENO
+
 # glob test
 
 res=$($com << 'EOF'
@@ -240,6 +249,39 @@ EOF
  2
   3' ] || err $LINENO
 
+res=$($com <<< 'echo `echo a  b`x; x=`echo v`; echo "$x `echo "c  d"`" "[`echo \"q\"`]"')
+[ "$res" = 'a bx
+v c  d [q]' ] || err $LINENO
+
+res=$($com <<< 'echo `echo \`echo nested\`` `echo \$HOME` `echo \\\\` "`dirname "/a/b"`" `` e')
+[ "$res" = "nested $HOME \\ /a e" ] || err $LINENO
+
+# process substitution
+
+printf 'b\na\n' > $tmp.a; printf 'a\nc\n' > $tmp.b
+res=$($com <<< 'diff <(sort '$tmp.a') <(sort '$tmp.b'); cat <(echo x) <(echo y); cat < <(echo r)')
+[ "$res" = "2c2
+< b
+---
+> c
+x
+y
+r" ] || err $LINENO
+rm -f $tmp.a $tmp.b
+
+res=$($com <<< 'echo abc | tee >(tr a-z A-Z) > /dev/null')
+[ "$res" = "ABC" ] || err $LINENO
+
+res=$($com <<< 'f () { cat $1; }; f <(echo a); echo <(true) | grep -c "^/dev/fd/[0-9]*$"')
+[ "$res" = "a
+1" ] || err $LINENO
+
+res=$($com <<< 'for f in <(echo a); do cat $f; done; [ -e $f ] || echo closed; case <(true) in $f) [ -e $f ] && echo open ;; esac; [ -e $f ] || echo closed')
+[ "$res" = "a
+closed
+open
+closed" ] || err $LINENO
+
 # expansion of tilde
 
 res=$($com <<< 'echo ~')
@@ -346,6 +388,187 @@ EOF
 )
 [ "$res" = "Sat Jan  1 00:00:00 UTC 2000" ] || err $LINENO
 
+# pattern operators
+
+res=$($com <<< 'f=dir/sub/file.tar.gz; echo ${f#*/} ${f##*/} ${f%.*} ${f%%.*} ${f%/*} ${f#nomatch}')
+[ "$res" = "sub/file.tar.gz file.tar.gz dir/sub/file.tar dir/sub/file dir/sub dir/sub/file.tar.gz" ] || err $LINENO
+
+res=$($com <<< 'f=dir/sub/file.tar.gz; echo ${f/sub/SUB} ${f//\//:} ${f/#dir/DIR} ${f/%gz/bz2} ${f/#sub/X}')
+[ "$res" = "dir/SUB/file.tar.gz dir:sub:file.tar.gz DIR/sub/file.tar.gz dir/sub/file.tar.bz2 dir/sub/file.tar.gz" ] || err $LINENO
+
+res=$($com <<< 'x=aaa; echo ${x/a/b} ${x//a/b} ${x/#/pre} ${x/%/post} ${x/a*/z}')
+[ "$res" = "baa bbb preaaa aaapost z" ] || err $LINENO
+
+res=$($com <<< 'y="a*b*c"; e=.txt; g=foo.txt; echo ${y//"*"/-} ${y#"a*"} ${g%$e}')
+[ "$res" = "a-b-c b*c foo" ] || err $LINENO
+
+res=$($com <<< 'arr=(a.c b.c c.h); echo ${arr[@]%.c} ${arr[@]/#/x-}')
+[ "$res" = "a b c.h x-a.c x-b.c x-c.h" ] || err $LINENO
+
+res=$($com <<< 'u=日本語テキスト; echo ${u#日本} ${u%テ*} ${u//本/ほん}')
+[ "$res" = "語テキスト 日本語 日ほん語テキスト" ] || err $LINENO
+
+res=$($com <<< 'z=abcabc; f=a/b; echo ${z//b?/Y} ${z//[ac]/-} "${z/b/"x y"}" ${f#${f%%/*}/}')
+[ "$res" = "aYaY -b--b- ax ycabc b" ] || err $LINENO
+
+# substring, length and case modification
+
+res=$($com <<< 's=日本語テキスト; echo ${#s} ${s:2} ${s:1:2} ${s: -3} ${s: -3:2} ${s:(-2)} ${s:1:-2} ${s:0:0}x')
+[ "$res" = "7 語テキスト 本語 キスト キス スト 本語テキ x" ] || err $LINENO
+
+res=$($com <<< 's=abcdefg; n=2; echo ${s:n} ${s:$n:n+1} ${s:n*2}')
+[ "$res" = "cdefg cde efg" ] || err $LINENO
+
+res=$($com <<< 's=abc; echo ${s:1:-5}; echo $?')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< 'x=hello; X=HELLO; echo ${x^} ${x^^} ${x^^[lo]} ${x^[e]} ${X,} ${X,,} ${X,,[HL]}')
+[ "$res" = "Hello HELLO heLLO hello hELLO hello hEllO" ] || err $LINENO
+
+res=$($com <<< 'f() { echo ${#@} ${#1} ${#4} ${@:2} ${@:1:2} ${@: -2} ${@^}; }; f a bb ccc dddd')
+[ "$res" = "4 1 4 bb ccc dddd a bb ccc dddd A Bb Ccc Dddd" ] || err $LINENO
+
+res=$($com <<< 'a=(one two three four); echo ${#a[@]} ${#a[1]} ${a[@]:1:2} ${a[@]: -1} ${a[@]^^} ${a[2]:1:3}')
+[ "$res" = "4 3 two three four ONE TWO THREE FOUR hre" ] || err $LINENO
+
+# unset and empty
+
+res=$($com <<< 'e=; echo [${u-def}] [${u:-def}] [${e-def}] [${e:-def}] [${e+alt}] [${e:+alt}] [${u+alt}]')
+[ "$res" = "[def] [def] [] [def] [alt] [] []" ] || err $LINENO
+
+res=$($com <<< 'echo ${v=set1} $v; e=; echo [${e=x}] [${e:=y}] $e')
+[ "$res" = "set1 set1
+[] [y] y" ] || err $LINENO
+
+res=$($com <<< 'echo "${x:-a\b}" "${x:-a\$b}" "${x:-'"'"'a b'"'"'}" "${x:-"q r"}" ${x:-'"'"'a b'"'"'} ${x:-a\b}')
+[ "$res" = "a\\b a\$b 'a b' q r a b ab" ] || err $LINENO
+
+res=$($com <<< 'a=(1 2); echo ${b:-${c:-nested}} ${a[5]-none} ${a[1]-none}')
+[ "$res" = "nested none 2" ] || err $LINENO
+
+res=$($com <<< 'e=; echo ${e:?}; echo after' 2>&1)
+[ "$res" = "bash: e: parameter null or not set" ] || err $LINENO
+
+res=$($com <<< 'echo ${u?}; echo after' 2>&1; echo $?)
+[ "$res" = "bash: u: parameter not set
+1" ] || err $LINENO
+
+res=$($com <<< 'set -u; echo ${u-ok}; f() { echo ${1:-none}; }; f; echo $u; echo after' 2>&1)
+[ "$res" = "ok
+none
+bash: u: unbound variable" ] || err $LINENO
+
+res=$($com <<< 'set -o nounset; set +u; echo [$u]; set -u; a=1; echo $a')
+[ "$res" = "[]
+1" ] || err $LINENO
+
+# indirect expansion and transformation
+
+res=$($com <<< 'x=y; y=hello; a=(a b c); r="a[1]"; echo ${!x} ${!x:-d} ${!x#he} ${!r}')
+[ "$res" = "hello hello llo b" ] || err $LINENO
+
+res=$($com <<< 'f() { echo ${!#} ${!1}; }; y=Y; f y z')
+[ "$res" = "z Y" ] || err $LINENO
+
+res=$($com <<< 'echo ${!z}; echo $?' 2>&1)
+[ "$res" = "bash: z: invalid indirect expansion
+1" ] || err $LINENO
+
+res=$($com <<< 'myv1=1 myv2=2; echo ${!myv*}; echo "${!myv@}"')
+[ "$res" = "myv1 myv2
+myv1 myv2" ] || err $LINENO
+
+res=$($com <<< 'v="a b'"'"'c"; e=; echo ${v@Q} ${e@Q} ${v@A}')
+[ "$res" = "'a b'\''c' '' v='a b'\''c'" ] || err $LINENO
+
+res=$($com <<< 'q="it'"'"'s \$HOME \\ \"x\""; eval "echo ${q@Q}"')
+[ "$res" = "it's \$HOME \\ \"x\"" ] || err $LINENO
+
+res=$($com <<< 'w="a\\x41\\\\b"; echo ${w@E}')
+[ "$res" = "aA\\b" ] || err $LINENO
+
+res=$($com <<< 'x=v; s='"'"'$x $(echo "c  d") $((1+2)) "q"'"'"'; echo "${s@P}"')
+[ "$res" = "v c  d 3 \"q\"" ] || err $LINENO
+
+res=$($com <<< 's=hello; echo ${s@U} ${s@u} ${s@L}; a=(1 "2 3"); echo ${a[@]@A}; echo ${a[@]@Q} ${a[@]@U}')
+[ "$res" = "HELLO Hello hello
+declare -a a=([0]=\"1\" [1]=\"2 3\")
+'1' '2 3' 1 2 3" ] || err $LINENO
+
+res=$($com <<< 'declare -A m; m[k]=v; a=(1); export XX=1; echo ${m@a} ${a@a} ${m[@]@A} ${XX@A}')
+[ "$res" = "A a declare -A m=([k]=\"v\" ) declare -x XX='1'" ] || err $LINENO
+
+# field splitting
+
+res=$($com <<< 'x=" a  b "; printf "[%s]" $x b$x"c" "$x"')
+[ "$res" = "[a][b][b][a][b][c][ a  b ]" ] || err $LINENO
+
+res=$($com <<< 'IFS=:; x=":a::b:"; printf "[%s]" $x; IFS=" :"; y="a : b"; printf "[%s]" $y')
+[ "$res" = "[][a][][b][a][b]" ] || err $LINENO
+
+res=$($com <<< 'x="a b"; IFS=; printf "[%s]" $x; unset IFS; printf "[%s]" $x')
+[ "$res" = "[a b][a][b]" ] || err $LINENO
+
+res=$($com <<< 'printf "[%s]" $(printf "a\nb") $((1+2)); set -- $e x; echo $#')
+[ "$res" = "[a][b][3]1" ] || err $LINENO
+
+res=$($com <<< 'x="a b"; y=$x; a=($x); [[ $y == "a b" ]] && echo ${#a[@]}; case $x in "a b") echo ok ;; esac')
+[ "$res" = "2
+ok" ] || err $LINENO
+
+res=$($com <<< 'IFS=+; x=1+2; echo $(($x)); a=(5 6); i=1+0; echo ${a[$i]}')
+[ "$res" = "3
+6" ] || err $LINENO
+
+# quoting
+
+mkdir -p $tmp.d
+touch "$tmp.d/a[1]" "$tmp.d/a\\b" "$tmp.d/abc"
+res=$($com <<< 'cd '$tmp.d'; echo "a["* a\\* '"'a?'*"' a?c; p="a*"; echo $p "$p" ${x:-"a*"}')
+[ "$res" = 'a[1] a\b a?* abc
+a[1] a\b abc a* a*' ] || err $LINENO
+rm -rf $tmp.d
+
+res=$($com <<< 'x='"'"'a\b'"'"'; echo $x "$x" '"'"'a\\b'"'"' "a\\b" "a\b"; y=$x; echo "$y"')
+[ "$res" = 'a\b a\b a\\b a\b a\b
+a\b' ] || err $LINENO
+
+res=$($com <<< 'q="a?c"; case abc in "$q") echo no ;; $q) echo yes ;; esac; case "a*" in "a*") echo yes ;; esac')
+[ "$res" = "yes
+yes" ] || err $LINENO
+
+res=$($com <<< 'x=; f="x y"; printf "[%s]" ${x:-$f} ${x:-"$f"} "${x:-"a  b"}"')
+[ "$res" = "[x][y][x y][a  b]" ] || err $LINENO
+
+res=$($com <<< 're="^v([0-9]+)\.([0-9]+)"; [[ v12.3 =~ $re ]] && echo ${BASH_REMATCH[1]} ${BASH_REMATCH[2]}; [[ v12x3 =~ $re ]] || echo no')
+[ "$res" = "12 3
+no" ] || err $LINENO
+
+res=$($com <<< 're='"'"'\.'"'"'; [[ a.c =~ $re ]] && echo a; [[ abc =~ $re ]] || echo b; re='"'"'a\+b'"'"'; [[ a+b =~ $re ]] && echo c')
+[ "$res" = "a
+b
+c" ] || err $LINENO
+
+mkdir -p $tmp.d
+touch "$tmp.d/d*" "$tmp.d/de"
+res=$($com <<< 'p='"'"'a\*'"'"'; x="a*b"; echo ${x#$p}; cd '$tmp.d'; x='"'"'d\'"'"'; echo $x* d*')
+[ "$res" = 'b
+d\* d* de' ] || err $LINENO
+rm -rf $tmp.d
+
+res=$($com <<< "printf '[%s]' \$'a\\tb' \$'\\x41\\101\\u3042' \$'it\\'s' \$'\\cA' \"\$'x'\" \$'*'")
+[ "$res" = "[a	b][AAあ][it's][$(printf '\001')][\$'x'][*]" ] || err $LINENO
+
+res=$($com <<< "echo \$'\\xe2\\x9c\\x93' \$'\\303\\251x'")
+[ "$res" = "✓ éx" ] || err $LINENO
+
+mkdir -p $tmp.d/fr/LC_MESSAGES
+printf '\xde\x12\x04\x95\x00\x00\x00\x00\x01\x00\x00\x00\x1c\x00\x00\x00\x24\x00\x00\x00\x00\x00\x00\x00\x2c\x00\x00\x00\x05\x00\x00\x00\x2c\x00\x00\x00\x0a\x00\x00\x00\x32\x00\x00\x00hello\x00bonjour $x\x00' > $tmp.d/fr/LC_MESSAGES/greet.mo
+res=$($com <<< 'x=Ann; TEXTDOMAIN=greet; TEXTDOMAINDIR='$tmp.d'; LANG=fr_FR.UTF-8; echo $"hello" $"bye $x"; LANG=C; echo $"hello"')
+[ "$res" = "bonjour Ann bye Ann
+hello" ] || err $LINENO
+rm -rf $tmp.d
+
 # special variable
 
 res=$($com <<< 'ls aaaaaaa; echo $?')
@@ -373,6 +596,86 @@ res=$($com <<< 'A=x;echo a $A; echo $_')
 [ "$res" = "a x
 x" ] || err $LINENO
 
+res=$($com <<< 'set -- "a b" "" c; printf "[%s]" "$@" "x$@y"; f () { echo $#; }; f "$@"; set --; f "$@" "x$@y"')
+[ "$res" = "[a b][][c][xa b][][cy]3
+1" ] || err $LINENO
+
+res=$($com <<< 'set -- "a  b" c; for a in "$@"; do echo "$a"; done; IFS=:; echo "$*"; IFS=; echo "$*"')
+[ "$res" = "a  b
+c
+a  b:c
+a  bc" ] || err $LINENO
+
+res=$($com <<< 'set -- "a b" c; x="$@"; echo "$x"; x="pre $@ post"; echo "$x"')
+[ "$res" = "a b c
+pre a b c post" ] || err $LINENO
+
+### ARRAY ###
+
+res=$($com <<< 'a=(x y z) ; echo ${a[0]} ${a[2]} ${#a[@]} $a')
+[ "$res" = "x z 3 x" ] || err $LINENO
+
+res=$($com <<< 'a=(x y z) ; a[5]=w ; echo ${a[@]} ; echo ${!a[@]} ; echo ${a[-1]}')
+[ "$res" = "x y z w
+0 1 2 5
+w" ] || err $LINENO
+
+res=$($com <<< 'a=(p q r); s="${a[@]}"; t=${a[@]}; echo "$s|$t"')
+[ "$res" = "p q r|p q r" ] || err $LINENO
+
+res=$($com <<< 'a=([2]=b [0]=a c); echo ${!a[@]} ${a[@]}')
+[ "$res" = "0 1 2 a c b" ] || err $LINENO
+
+res=$($com <<< 'a=(x) ; a+=(p "q r") ; for e in "${a[@]}" ; do echo "[$e]" ; done')
+[ "$res" = "[x]
+[p]
+[q r]" ] || err $LINENO
+
+res=$($com <<< 'a=(x y z) ; unset "a[1]" ; i=1 ; echo ${!a[*]} ${a[i+1]} ${a[$i+1]}')
+[ "$res" = "0 2 z z" ] || err $LINENO
+
+res=$($com <<< 'b=() ; for e in "${b[@]}" ; do echo bad ; done ; c=(1 2 3) ; IFS=, ; echo "${c[*]}"')
+[ "$res" = "1,2,3" ] || err $LINENO
+
+res=$($com <<< 'a=([2]=two [0]=zero three) ; a[0]+=s ; echo ${!a[@]} ${a[@]}')
+[ "$res" = "0 1 2 zeros three two" ] || err $LINENO
+
+res=$($com << 'EOF'
+m=(
+  one  # comment
+  two
+)
+echo ${m[1]}
+unset m
+echo "${#m[@]}"
+EOF
+)
+[ "$res" = "two
+0" ] || err $LINENO
+
+res=$($com <<< 'a=(x) ; a[-5]=y ; echo $?')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< 'declare -A m ; m[one]=1 ; m["two words"]=2 ; k=three ; m[$k]=3 ; echo ${m[one]} ${m["two words"]} ${m[$k]} ${#m[@]}')
+[ "$res" = "1 2 3 3" ] || err $LINENO
+
+res=$($com <<< 'declare -A m ; m=([a]=x [b]=y) ; m+=([c]=z) ; m[a]+=X ; unset "m[b]" ; for k in "${!m[@]}" ; do echo "$k=${m[$k]}" ; done')
+[ "$res" = "a=xX
+c=z" ] || err $LINENO
+
+res=$($com <<< 'declare -A m ; i=1 ; m[i+1]=q ; echo ${!m[@]} ${m[@]}')
+[ "$res" = "i+1 q" ] || err $LINENO
+
+res=$($com <<< 'a=(1) ; declare -A a ; echo $?')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< 'declare -A m=([k1]=v1 [k2]="v 2") ; declare -a a=(x y) ; y="p q" ; declare z=$y ; echo ${m[k1]} ${m[k2]} ${a[1]} "$z"')
+[ "$res" = "v1 v 2 y p q" ] || err $LINENO
+
+res=$($com <<< 'a=(1 2) ; declare -A m ; m[k]=1 ; [[ -v a[1] ]] && [[ -v m[k] ]] && [[ -v a ]] && echo ok ; [[ -v a[5] ]] || [[ -v m[z] ]] || echo ng')
+[ "$res" = "ok
+ng" ] || err $LINENO
+
 ### REDIRECTION ###
 
 res=$($com << 'EOF'
@@ -460,6 +763,77 @@ EOF
 )
 [ "$?" = "1" ] || err $LINENO
 
+# here document
+
+res=$($com << 'EOF'
+A=world
+cat <<END
+hello $A $(echo a) `echo b` $((1+2)) \$A \\ "q" \"x\"
+c\
+d
+END
+cat << 'END'
+$A `x` \$
+END
+EOF
+)
+[ "$res" = 'hello world a b 3 $A \ "q" \"x\"
+cd
+$A `x` \$' ] || err $LINENO
+
+res=$($com << 'EOF'
+A=x
+cat <<-END | tr a-z A-Z
+		tab
+	END
+cat <<A; cat <<"B"
+one $A
+A
+two $A
+B
+EOF
+)
+[ "$res" = 'TAB
+one x
+two $A' ] || err $LINENO
+
+res=$($com << 'EOF'
+for i in 1 2; do
+cat <<END
+i=$i
+END
+done
+f () {
+  cat <<END > /tmp/$$-heredoc
+in f $1
+END
+  cat /tmp/$$-heredoc; rm /tmp/$$-heredoc
+}
+f x; f y
+EOF
+)
+[ "$res" = "i=1
+i=2
+in f x
+in f y" ] || err $LINENO
+
+res=$($com << 'EOF'
+x=$(cat <<END
+in cs
+END
+)
+echo $x
+eval "cat <<END
+in eval
+END"
+cat <<END
+end
+EOF
+)
+[ "$res" = "in cs
+in eval
+end" ] || err $LINENO
+
 ### PIPELINE ###
 
 res=$($com <<< 'echo abc | rev')
@@ -581,6 +955,101 @@ res=$($com <<< 'echo $((1+2/3))')
 res=$($com <<< 'echo $((-1+2/3))')
 [ "$res" = "-1" ] || err $LINENO
 
+res=$($com <<< 'echo $((7%3)) $((2**10)) $((-2**2)) $((2**3**2))')
+[ "$res" = "1 1024 4 512" ] || err $LINENO
+
+res=$($com <<< 'echo $((1<<4)) $((256>>2)) $((3<5)) $((3>=5)) $((2==2)) $((2!=2))')
+[ "$res" = "16 64 1 0 1 0" ] || err $LINENO
+
+res=$($com <<< 'echo $((6&3)) $((6^3)) $((6|3)) $((~5)) $((!0)) $((!7)) $((+3))')
+[ "$res" = "2 5 7 -6 1 0 3" ] || err $LINENO
+
+res=$($com <<< 'echo $(( (1+2)*3 )) $((1?2:3)) $((0?2:3)) $((1,2,3))')
+[ "$res" = "9 2 3 3" ] || err $LINENO
+
+res=$($com <<< 'echo $((0 && 1/0)) $((1 || 1/0)) $((1 ? 4 : 1/0))')
+[ "$res" = "0 1 4" ] || err $LINENO
+
+res=$($com <<< 'echo $((1+)); echo $?')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< '((1/0)); echo $?')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< '((5>3)) && echo yes')
+[ "$res" = "yes" ] || err $LINENO
+
+res=$($com <<< '((echo hoge) )')
+[ "$res" = "hoge" ] || err $LINENO
+
+res=$($com <<< 'x=3; e=; echo $((x+1)) $(($x*2)) $((undefined+1)) $((e+2))')
+[ "$res" = "4 6 1 2" ] || err $LINENO
+
+res=$($com <<< 'a=b; b=c; c=5; q=c+1; echo $((a*2)) $((q*3))')
+[ "$res" = "10 18" ] || err $LINENO
+
+res=$($com <<< 'n=0; while ((n<3)); do echo -n $n; ((n++)); done')
+[ "$res" = "012" ] || err $LINENO
+
+res=$($com <<< 'i=5; echo $((i++)) $i $((++i)) $((i--)) $((--i)) $i')
+[ "$res" = "5 6 7 7 5 5" ] || err $LINENO
+
+res=$($com <<< 'v=10; ((v+=5)); ((v-=3, v*=2)); echo $v; ((v/=4)); ((v%=4)); echo $v')
+[ "$res" = "24
+2" ] || err $LINENO
+
+res=$($com <<< 'w=1; ((w<<=4)); ((w>>=2)); ((w|=3)); echo $w; ((w&=6)); ((w^=7)); echo $w')
+[ "$res" = "7
+1" ] || err $LINENO
+
+res=$($com <<< 'echo $((z=4)) $z; k=0; echo $((1 || (k=5))) $k')
+[ "$res" = "4 4
+1 0" ] || err $LINENO
+
+res=$($com <<< 'arr=(1 2 3); i=2; echo $((arr[1]+arr[i])); ((arr[0]+=10)); echo ${arr[@]}')
+[ "$res" = "5
+11 2 3" ] || err $LINENO
+
+res=$($com <<< 'echo $((1=2)); echo $?')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< 'x=3; [[ x+1 -eq 4 ]] && echo ok')
+[ "$res" = "ok" ] || err $LINENO
+
+res=$($com <<< 'echo $((2**40)) $((9223372036854775807+1))')
+[ "$res" = "1099511627776 -9223372036854775808" ] || err $LINENO
+
+res=$($com <<< 'echo $((0x1F)) $((017)) $((2#1010)) $((16#ff)) $((36#z)) $((64#_)) $((37#A))')
+[ "$res" = "31 15 10 255 35 63 36" ] || err $LINENO
+
+res=$($com <<< 'echo $((09)); echo $((65#1)); echo $?')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< 'echo $((5%0+3)) 2>&1 ; echo $?' 2>&1)
+[ "$res" = "bash: 5%0+3: division by 0 (error token is \"0+3\")
+1" ] || err $LINENO
+
+res=$($com <<< 'false; echo $((0)) $?')
+[ "$res" = "0 1" ] || err $LINENO
+
+res=$($com <<< '((1)) && echo and; ((0)) || echo or; ! ((0)) && echo neg; ! ((1)); echo $?')
+[ "$res" = "and
+or
+neg
+1" ] || err $LINENO
+
+res=$($com <<< 'if ((3>2)); then echo if; fi; n=0; while ((n<2)); do ((n++)); echo w$n; done')
+[ "$res" = "if
+w1
+w2" ] || err $LINENO
+
+res=$($com <<< '((1/0)) 2> /dev/null; echo $?; echo x | ((0)); echo $?')
+[ "$res" = "1
+1" ] || err $LINENO
+
+res=$($com <<< '((1/0)) 2>&1 | cat')
+[ "$res" = "bash: ((: 1/0: division by 0 (error token is \"0\")" ] || err $LINENO
+
 ### MULTILINE INPUT ###
 
 res=$($com << 'EOF'
@@ -877,6 +1346,69 @@ res=$($com <<< 'if [ "$(cat)" == "xx" ] ; then echo xyz; elif [ "b" == "b" ] ; t
 [ "$res" = "pqr" ] || err $LINENO
 rm -f $tmp 
 
+### [[ ]] ###
+
+res=$($com <<< '[[ a == a ]] ; echo $? ; [[ a == b ]] ; echo $?')
+[ "$res" = "0
+1" ] || err $LINENO
+
+res=$($com <<< '[[ abc == a* ]] && echo x ; [[ abc == "a*" ]] || echo y')
+[ "$res" = "x
+y" ] || err $LINENO
+
+res=$($com <<< 'A="a b" ; [[ $A == "a b" ]] && echo ok ; [[ -n $B ]] || echo empty')
+[ "$res" = "ok
+empty" ] || err $LINENO
+
+res=$($com <<< 'A=* ; [[ $A == "*" ]] && echo star')
+[ "$res" = "star" ] || err $LINENO
+
+res=$($com <<< '[[ 1 -lt 2 && ( a > b || ! -d / ) ]] ; echo $?')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< '[[ -f ./test.bash ]] && [[ -d ../src ]] && [[ ! -e /aaaaa ]] && echo ok')
+[ "$res" = "ok" ] || err $LINENO
+
+res=$($com <<< '[[ a < b ]] && echo lt ; [[ 10 -eq 010 ]] && echo eq')
+[ "$res" = "lt
+eq" ] || err $LINENO
+
+res=$($com <<< 'if [[ a != b ]] ; then echo yes ; fi | rev')
+[ "$res" = "sey" ] || err $LINENO
+
+res=$($com <<< '[[ a ==
+a ]] && echo ok')
+[ "$res" = "ok" ] || err $LINENO
+
+res=$($com <<< 's=v1.23 ; [[ $s =~ ^v([0-9]+)\.([0-9]+)$ ]] ; echo $? $BASH_REMATCH')
+[ "$res" = "0 v1.23" ] || err $LINENO
+
+res=$($com <<< '[[ abc =~ "a.c" ]] ; echo $? ; [[ a.c =~ "a.c" ]] ; echo $?')
+[ "$res" = "1
+0" ] || err $LINENO
+
+res=$($com <<< 're="^(ab)+$" ; [[ ababab =~ $re ]] && [[ abc =~ a(x|b)c ]] && [[ aa =~ ^a{2}$ ]] && echo ok')
+[ "$res" = "ok" ] || err $LINENO
+
+res=$($com <<< '[[ a =~ a{1 ]] ; echo $?')
+[ "$res" = "2" ] || err $LINENO
+
+res=$($com <<< '[[ a =~ ( ]]
+echo $?
+[[ a =~ a( ]] ; echo no
+echo $?')
+[ "$res" = "2
+2" ] || err $LINENO
+
+res=$($com <<< '[[ -f ]]
+echo $?
+[[ -n && a ]]
+echo $?
+[[ "-f" ]] ; echo $?')
+[ "$res" = "2
+2
+0" ] || err $LINENO
+
 ### GLOB FOR CASE ###
 
 res=$($com <<< 'glob_test "a*" abcde')
@@ -927,6 +1459,86 @@ res=$($com <<< 'seq 3 | while read x ; do echo $x🎂 ; done')
 2🎂
 3🎂" ] || err $LINENO
 
+### UNTIL ###
+
+res=$($com <<< 'n=a ; until [ $n = aaa ] ; do n=${n}a ; echo $n ; done')
+[ "$res" = "aa
+aaa" ] || err $LINENO
+
+res=$($com <<< 'until true ; do echo a ; done ; echo b')
+[ "$res" = "b" ] || err $LINENO
+
+### SELECT ###
+
+printf '1\n\n5\n' > /tmp/tmp_x
+res=$($com <<< 'select x in a b ; do echo "x=$x r=$REPLY" ; done < /tmp/tmp_x 2>&1 ; echo $?')
+[ "$res" = "1) a
+2) b
+#? x=a r=1
+#? 1) a
+2) b
+#? x= r=5
+#? 
+1" ] || err $LINENO
+
+res=$($com <<< 'PS3="> " ; select x in a b ; do echo $x ; done < /tmp/tmp_x 2>&1 | grep -c "^> a$"')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< '{ PS3="" ; select x in a b ; do echo $x ; break ; done ; cat ; } < /tmp/tmp_x 2>/dev/null')
+[ "$res" = "a
+
+5" ] || err $LINENO
+
+res=$($com <<< 'PS3="" ; select x in a ; do break ; done < /tmp/tmp_x 2>&1')
+[ "$res" = "1) a" ] || err $LINENO
+
+rm /tmp/tmp_x
+
+### FOR ###
+
+res=$($com <<< 'for i in a b c ; do echo $i ; done')
+[ "$res" = "a
+b
+c" ] || err $LINENO
+
+res=$($com << 'EOF'
+for f in t*t.bash
+do
+	echo $f
+done
+EOF
+)
+[ "$res" = "test.bash" ] || err $LINENO
+
+res=$($com <<< 'for i in {1,2}x "a b" ; do echo "[$i]" ; done | tr -d \\n')
+[ "$res" = "[1x][2x][a b]" ] || err $LINENO
+
+res=$($com <<< 'set a b ; for i ; do echo $i ; done')
+[ "$res" = "a
+b" ] || err $LINENO
+
+res=$($com <<< 'for i in ; do echo $i ; done ; echo $?')
+[ "$res" = "0" ] || err $LINENO
+
+res=$($com <<< 'for i in x y ; do echo $i ; done > /tmp/tmp_x ; cat /tmp/tmp_x ; rm /tmp/tmp_x')
+[ "$res" = "x
+y" ] || err $LINENO
+
+res=$($com <<< 'for i in a b ; do echo $i ; done & wait')
+echo "$res" | grep -q '^b$' || err $LINENO
+
+res=$($com <<< 'for ((;0;)) ; do echo a ; done ; echo b')
+[ "$res" = "b" ] || err $LINENO
+
+res=$($com <<< 'for (( 1 ; 0 ; 1 ))
+do
+	echo a
+done | wc -l')
+[ "$res" = "0" ] || err $LINENO
+
+res=$($com <<< 'for ((1;1)) ; do echo a ; done')
+[ "$?" = "1" ] || err $LINENO
+
 ### CASE ###
 
 res=$($com <<< 'case $- in *x*) echo x ;; *) echo no ;; esac')
@@ -959,6 +1571,24 @@ EOF
 )
 [ "$res" = "no" ] || err $LINENO
 
+res=$($com <<< 'case a in a) echo 1 ;& b) echo 2 ;& c) echo 3 ;; d) echo 4 ;; esac')
+[ "$res" = "1
+2
+3" ] || err $LINENO
+
+res=$($com <<< 'case ab in a*) echo 1 ;;& *b) echo 2 ;;& x) echo 3 ;; *) echo 4 ;; esac')
+[ "$res" = "1
+2
+4" ] || err $LINENO
+
+res=$($com <<< 'p=ab ; case abc in "$p"*) echo prefix ;; esac ; case abc in "a*") echo lit ;; ( x | abc ) echo ok ;; esac')
+[ "$res" = "prefix
+ok" ] || err $LINENO
+
+res=$($com <<< 'case 3 in $((1+2))) echo arith ;; esac ; case ab in "a?") echo bad ;; esac ; echo $?')
+[ "$res" = "arith
+0" ] || err $LINENO
+
 cat << EOF > $tmp 
 echo hoge
 EOF